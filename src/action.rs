@@ -1,15 +1,72 @@
-use crate::ProjectWC;
-use smithay::{
-    desktop::Window, reexports::wayland_protocols::xdg::shell::server::xdg_toplevel,
-    utils::SERIAL_COUNTER,
+use crate::{
+    ProjectWC,
+    layout::LayoutType,
+    workspace::{ALL_TAGS, TagMask},
 };
+use serde::{Deserialize, Deserializer, de::Error as _};
+use smithay::desktop::Window;
 
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Action {
+    Quit,
+    #[serde(deserialize_with = "deserialize_argv")]
+    Spawn(Vec<String>),
+    CloseWindow,
     FocusNext,
     FocusPrevious,
+    /// Focuses the nearest window on screen in this direction.
+    FocusDirection(Direction),
+    /// Swaps the focused tile with its nearest neighbour in this direction.
+    MoveDirection(Direction),
+    /// Opens the window switcher, or selects the next window in it.
+    SwitchWindows,
+    SwitchWindowsBackward,
+    /// Swaps the focused window with the master, or the master with the
+    /// next tile.
+    Zoom,
+    MoveNext,
+    MovePrevious,
+    /// Swaps the focused window with the tile at this index, the master
+    /// being 0.
+    SwapWith(usize),
+    ReloadConfig,
+    View(TagMask),
+    ToggleView(TagMask),
+    /// Puts the focused window on exactly these tags.
+    Tag(TagMask),
+    ToggleTag(TagMask),
+    ViewAll,
+    ViewPrevious,
+    /// Switches the viewed workspace to this layout.
+    SetLayout(LayoutType),
+    CycleLayout,
+    /// Adds to the master factor, clamped to 0.05..=0.95.
+    #[serde(deserialize_with = "deserialize_delta")]
+    IncMasterFactor(f32),
+    /// Adds to the number of master windows, never going below zero.
+    IncNumMaster(i32),
+    ResetMasterFactor,
+    ResetNumMaster,
+    /// Adds to the focused window's size weight, clamped to 0.25..=4.0.
+    #[serde(deserialize_with = "deserialize_delta")]
+    IncWindowFactor(f32),
+    ResetWindowFactor,
+    ToggleFloating,
+    ToggleFullscreen,
 }
 
-enum Direction {
+/// A way to look from one window to its neighbours on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+enum Step {
     Next,
     Previous,
 }
@@ -17,54 +74,174 @@ enum Direction {
 impl Action {
     pub fn execute(self, project_wc: &mut ProjectWC) {
         match self {
+            Action::Quit => {
+                tracing::debug!("Quitting");
+                project_wc.loop_signal.stop();
+            }
+            Action::Spawn(argv) => {
+                tracing::debug!(?argv, "Spawning");
+                if let Err(err) = std::process::Command::new(&argv[0])
+                    .args(&argv[1..])
+                    .spawn()
+                {
+                    tracing::warn!("failed to spawn {}: {err}", argv[0]);
+                }
+            }
+            Action::CloseWindow => {
+                if let Some(window) = project_wc.focused_window() {
+                    tracing::info!("Closing focused window");
+                    window.toplevel().unwrap().send_close();
+                }
+            }
             Action::FocusNext => {
-                change_focus(Direction::Next, project_wc);
+                change_focus(Step::Next, project_wc);
             }
             Action::FocusPrevious => {
-                change_focus(Direction::Previous, project_wc);
+                change_focus(Step::Previous, project_wc);
+            }
+            Action::FocusDirection(direction) => {
+                if let Some(target) = project_wc
+                    .focused_window()
+                    .and_then(|window| project_wc.window_in_direction(&window, direction))
+                {
+                    project_wc.focus_window_by_keyboard(&target);
+                }
+            }
+            Action::MoveDirection(direction) => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.move_in_direction(&window, direction);
+                }
+            }
+            Action::SwitchWindows => {
+                project_wc.switch_window(true);
+            }
+            Action::SwitchWindowsBackward => {
+                project_wc.switch_window(false);
+            }
+            Action::Zoom => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.zoom(&window);
+                }
+            }
+            Action::MoveNext => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.move_window(&window, 1);
+                }
+            }
+            Action::MovePrevious => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.move_window(&window, -1);
+                }
+            }
+            Action::SwapWith(index) => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.swap_with(&window, index);
+                }
+            }
+            Action::ReloadConfig => {
+                // Errors are logged, the previous config stays active
+                project_wc.reload_config().ok();
+            }
+            Action::View(mask) => {
+                project_wc.view(mask.0);
+            }
+            Action::ToggleView(mask) => {
+                project_wc.toggle_view(mask.0);
+            }
+            Action::Tag(mask) => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.tag_window(&window, mask.0);
+                }
+            }
+            Action::ToggleTag(mask) => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.toggle_window_tag(&window, mask.0);
+                }
+            }
+            Action::ViewAll => {
+                project_wc.view(ALL_TAGS);
+            }
+            Action::ViewPrevious => {
+                project_wc.view_previous();
+            }
+            Action::SetLayout(layout_type) => {
+                project_wc.set_layout(layout_type);
+            }
+            Action::CycleLayout => {
+                project_wc.cycle_layout();
+            }
+            Action::IncMasterFactor(delta) => {
+                project_wc.inc_master_factor(delta);
+            }
+            Action::IncNumMaster(delta) => {
+                project_wc.inc_num_master(delta);
+            }
+            Action::ResetMasterFactor => {
+                project_wc.reset_master_factor();
+            }
+            Action::ResetNumMaster => {
+                project_wc.reset_num_master();
+            }
+            Action::IncWindowFactor(delta) => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.inc_window_factor(&window, delta);
+                }
+            }
+            Action::ResetWindowFactor => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.reset_window_factor(&window);
+                }
+            }
+            Action::ToggleFloating => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.toggle_floating(&window);
+                }
+            }
+            Action::ToggleFullscreen => {
+                if let Some(window) = project_wc.focused_window() {
+                    project_wc.toggle_fullscreen(&window);
+                }
             }
         };
     }
 }
 
-fn change_focus(direction: Direction, project_wc: &mut ProjectWC) {
-    let keyboard = project_wc.seat.get_keyboard().unwrap();
-    let serial = SERIAL_COUNTER.next_serial();
+fn deserialize_argv<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let argv = Vec::<String>::deserialize(deserializer)?;
+    if argv.is_empty() {
+        return Err(D::Error::custom("spawn needs at least a program name"));
+    }
+    Ok(argv)
+}
 
-    let windows: Vec<Window> = project_wc.space.elements().cloned().collect();
-    if windows.is_empty() {
-        return;
+/// TOML allows `nan` and `inf`, which clamping can't turn back into a
+/// usable factor.
+fn deserialize_delta<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let delta = f32::deserialize(deserializer)?;
+    if !delta.is_finite() {
+        return Err(D::Error::custom(format!(
+            "factor change must be a finite number, got {delta}"
+        )));
     }
+    Ok(delta)
+}
 
-    let current_focus = keyboard.current_focus();
-    let current_idx = current_focus.and_then(|surf| {
-        project_wc
-            .window_for_surface(&surf)
-            .and_then(|w| windows.iter().position(|win| win == &w))
-    });
+fn change_focus(step: Step, project_wc: &mut ProjectWC) {
+    // Raising the target reorders the space, so cycle in tiling order instead
+    let windows: Vec<Window> = project_wc.visible_windows();
+    let len = windows.len();
+    let current_idx = project_wc
+        .focused_window()
+        .and_then(|focused| windows.iter().position(|win| win == &focused));
 
-    let target_idx = match (direction, current_idx) {
-        (Direction::Next, Some(i)) => usize::min(i + 1, windows.len() - 1),
-        (Direction::Previous, Some(i)) => i.saturating_sub(1),
+    let wrap = project_wc.config.focus.wrap;
+    let target_idx = match (step, current_idx) {
+        (Step::Next, Some(i)) if wrap => (i + 1) % len,
+        (Step::Previous, Some(i)) if wrap => (i + len - 1) % len,
+        (Step::Next, Some(i)) => usize::min(i + 1, len - 1),
+        (Step::Previous, Some(i)) => i.saturating_sub(1),
         _ => return,
     };
 
-    if let Some(prev_idx) = current_idx {
-        if let Some(prev_toplevel) = windows[prev_idx].toplevel() {
-            prev_toplevel.with_pending_state(|state| {
-                state.states.unset(xdg_toplevel::State::Activated);
-            });
-            prev_toplevel.send_pending_configure();
-        }
-    }
-
-    let target = &windows[target_idx];
-
-    if let Some(toplevel) = target.toplevel() {
-        toplevel.with_pending_state(|state| {
-            state.states.set(xdg_toplevel::State::Activated);
-        });
-        toplevel.send_pending_configure();
-        keyboard.set_focus(project_wc, Some(toplevel.wl_surface().clone()), serial);
-    }
+    project_wc.focus_window_by_keyboard(&windows[target_idx]);
 }
@@ -0,0 +1,202 @@
+use smithay::{
+    backend::{allocator::Fourcc, renderer::element::memory::MemoryRenderBuffer},
+    utils::Transform,
+};
+
+/// Pixels each font pixel is drawn as.
+const ZOOM: i32 = 2;
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+/// Width of one character cell, including the gap after it.
+const ADVANCE: i32 = (GLYPH_WIDTH + 1) * ZOOM;
+/// Height of one line of text, including the gap below it.
+pub const LINE_HEIGHT: i32 = (GLYPH_HEIGHT + 2) * ZOOM;
+
+/// Printable ASCII from `' '` to `'~'` in a 5x7 font. Each byte is a
+/// column, with the lowest bit at the top.
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00],
+    [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1c, 0x00],
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08],
+    [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39],
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e],
+    [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e], [0x7e, 0x11, 0x11, 0x11, 0x7e],
+    [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41],
+    [0x7f, 0x09, 0x09, 0x01, 0x01], [0x3e, 0x41, 0x41, 0x51, 0x32],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40], [0x7f, 0x02, 0x04, 0x02, 0x7f],
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e],
+    [0x7f, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x7f, 0x20, 0x18, 0x20, 0x7f],
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x03, 0x04, 0x78, 0x04, 0x03],
+    [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x00, 0x7f, 0x41, 0x41],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x41, 0x41, 0x7f, 0x00, 0x00],
+    [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f], [0x38, 0x54, 0x54, 0x54, 0x18],
+    [0x08, 0x7e, 0x09, 0x01, 0x02], [0x08, 0x14, 0x54, 0x54, 0x3c],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00],
+    [0x20, 0x40, 0x44, 0x3d, 0x00], [0x00, 0x7f, 0x10, 0x28, 0x44],
+    [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08], [0x08, 0x14, 0x14, 0x18, 0x7c],
+    [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c],
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00],
+    [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+/// Text drawn with the built-in bitmap font, one line per entry.
+///
+/// The rendered buffer is kept so that the damage tracker only sees a
+/// change when the text does.
+pub struct Label {
+    lines: Vec<String>,
+    width: i32,
+    buffer: MemoryRenderBuffer,
+}
+
+impl Label {
+    /// `lines` cut to fit into `width` pixels, each in its own colour.
+    pub fn new(lines: &[(&str, [u8; 4])], width: i32) -> Self {
+        let texts: Vec<String> = lines.iter().map(|(text, _)| fit(text, width)).collect();
+        let height = LINE_HEIGHT * lines.len() as i32;
+        let mut pixels = vec![0u8; (width.max(1) * height.max(1) * 4) as usize];
+
+        for (row, (text, (_, color))) in texts.iter().zip(lines).enumerate() {
+            let top = row as i32 * LINE_HEIGHT;
+            // Centered, like the thumbnails above
+            let left = (width - text.chars().count() as i32 * ADVANCE) / 2;
+            for (i, c) in text.chars().enumerate() {
+                draw_glyph(
+                    &mut pixels,
+                    width,
+                    left + i as i32 * ADVANCE,
+                    top,
+                    c,
+                    *color,
+                );
+            }
+        }
+
+        let buffer = MemoryRenderBuffer::from_slice(
+            &pixels,
+            Fourcc::Argb8888,
+            (width.max(1), height.max(1)),
+            1,
+            Transform::Normal,
+            None,
+        );
+        Self {
+            lines: texts,
+            width,
+            buffer,
+        }
+    }
+
+    /// Whether this label already shows `lines` at `width`.
+    pub fn shows(&self, lines: &[(&str, [u8; 4])], width: i32) -> bool {
+        self.width == width
+            && self.lines.len() == lines.len()
+            && self
+                .lines
+                .iter()
+                .zip(lines)
+                .all(|(shown, (text, _))| *shown == fit(text, width))
+    }
+
+    pub fn buffer(&self) -> &MemoryRenderBuffer {
+        &self.buffer
+    }
+}
+
+/// `text` shortened with an ellipsis until it fits into `width` pixels.
+fn fit(text: &str, width: i32) -> String {
+    let chars: Vec<char> = text.chars().flat_map(to_ascii).collect();
+    let fitting = (width / ADVANCE).max(0) as usize;
+    if chars.len() <= fitting {
+        return chars.into_iter().collect();
+    }
+
+    let kept = fitting.saturating_sub(3);
+    let mut fitted: String = chars[..kept].iter().collect();
+    fitted.push_str(&"..."[..fitting - kept]);
+    fitted
+}
+
+/// Latin letters with accents, and the same letters without them.
+const ACCENTED: &str = "ÀÁÂÃÄÅàáâãäåÇçÈÉÊËèéêëÌÍÎÏìíîïÑñÒÓÔÕÖØòóôõöøÙÚÛÜùúûüÝýÿ";
+const UNACCENTED: &str = "AAAAAAaaaaaaCcEEEEeeeeIIIIiiiiNnOOOOOOooooooUUUUuuuuYyy";
+
+/// `c` spelled with characters the font has. Common typographic
+/// punctuation gets its ASCII look-alike and accents are dropped from
+/// Latin letters, anything else shows as `?`.
+fn to_ascii(c: char) -> Vec<char> {
+    if let Some(plain) = ACCENTED
+        .chars()
+        .zip(UNACCENTED.chars())
+        .find_map(|(accented, plain)| (accented == c).then_some(plain))
+    {
+        return vec![plain];
+    }
+
+    match c {
+        ' '..='~' => vec![c],
+        '\u{a0}' | '\u{2002}'..='\u{200a}' => vec![' '],
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => vec!['-'],
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{2032}' => vec!['\''],
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{2033}' => vec!['"'],
+        '\u{2026}' => vec!['.'; 3],
+        '\u{2022}' | '\u{00b7}' => vec!['*'],
+        '\u{00ab}' => vec!['<', '<'],
+        '\u{00bb}' => vec!['>', '>'],
+        _ => vec!['?'],
+    }
+}
+
+/// Draws `c` with its top left corner at `(x, y)` into an ARGB image that
+/// is `width` pixels wide.
+fn draw_glyph(pixels: &mut [u8], width: i32, x: i32, y: i32, c: char, color: [u8; 4]) {
+    let glyph = &FONT[c as usize - ' ' as usize];
+    // Argb8888 is stored little-endian
+    let [r, g, b, a] = color;
+
+    for (col, bits) in glyph.iter().enumerate() {
+        for row in 0..GLYPH_HEIGHT {
+            if bits & (1 << row) == 0 {
+                continue;
+            }
+            for dy in 0..ZOOM {
+                for dx in 0..ZOOM {
+                    let px = x + col as i32 * ZOOM + dx;
+                    let py = y + row * ZOOM + dy;
+                    if px < 0 || px >= width {
+                        continue;
+                    }
+                    let offset = ((py * width + px) * 4) as usize;
+                    pixels[offset..offset + 4].copy_from_slice(&[b, g, r, a]);
+                }
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod label;
 pub mod winit;
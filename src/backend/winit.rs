@@ -1,24 +1,56 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use smithay::{
     backend::{
         allocator::Fourcc,
         renderer::{
-            ExportMem,
+            ExportMem, ImportAll, ImportMem,
             damage::OutputDamageTracker,
-            element::surface::WaylandSurfaceRenderElement,
+            element::{
+                AsRenderElements, Kind,
+                memory::MemoryRenderBufferRenderElement,
+                render_elements,
+                solid::{SolidColorBuffer, SolidColorRenderElement},
+                surface::WaylandSurfaceRenderElement,
+                utils::RescaleRenderElement,
+            },
             gles::{GlesRenderer, GlesTarget},
         },
         winit::{self, WinitEvent},
     },
+    desktop::{LayerMap, Window, layer_map_for_output},
     output::{Mode, Output, PhysicalProperties, Subpixel},
     reexports::calloop::EventLoop,
     reexports::wayland_server::protocol::wl_shm::Format,
-    utils::{Physical, Rectangle, Size, Transform},
-    wayland::shm,
+    utils::{Logical, Physical, Point, Rectangle, Scale, Size, Transform},
+    wayland::{shell::wlr_layer::Layer, shm},
 };
 
-use crate::{CompositorError, ProjectWC, Result, protocols::wlr_screencopy::Screencopy};
+use crate::{
+    CompositorError, ProjectWC, Result, backend::label::Label,
+    protocols::wlr_screencopy::Screencopy, rules::title_and_app_id,
+};
+
+render_elements! {
+    pub OutputRenderElements<R> where R: ImportAll + ImportMem;
+    Surface = WaylandSurfaceRenderElement<R>,
+    Solid = SolidColorRenderElement,
+    Thumbnail = RescaleRenderElement<WaylandSurfaceRenderElement<R>>,
+    Label = MemoryRenderBufferRenderElement<R>,
+}
+
+/// Buffers for the compositor's own elements. They are kept across frames
+/// so the damage tracker can tell when they change.
+#[derive(Default)]
+struct OverlayBuffers {
+    drop_preview: SolidColorBuffer,
+    switcher_panel: SolidColorBuffer,
+    switcher_selection: SolidColorBuffer,
+    /// Top, bottom, left and right border of each window.
+    borders: HashMap<Window, [SolidColorBuffer; 4]>,
+    /// Title and app id of each window in the switcher.
+    switcher_labels: HashMap<Window, Label>,
+}
 
 pub fn init_winit(event_loop: &mut EventLoop<ProjectWC>, state: &mut ProjectWC) -> Result<()> {
     let (mut winit_backend, winit) =
@@ -45,6 +77,7 @@ pub fn init_winit(event_loop: &mut EventLoop<ProjectWC>, state: &mut ProjectWC)
     state.space.map_output(&output, (0, 0));
 
     let mut damage_tracker = OutputDamageTracker::from_output(&output);
+    let mut overlay_buffers = OverlayBuffers::default();
 
     // Set WAYLAND_DISPLAY for child processes
     unsafe { std::env::set_var("WAYLAND_DISPLAY", &state.socket_name) };
@@ -74,23 +107,16 @@ pub fn init_winit(event_loop: &mut EventLoop<ProjectWC>, state: &mut ProjectWC)
                 {
                     let (renderer, mut framebuffer) =
                         winit_backend.bind().expect("failed to bind winit window");
-                    smithay::desktop::space::render_output::<
-                        _,
-                        WaylandSurfaceRenderElement<GlesRenderer>,
-                        _,
-                        _,
-                    >(
-                        &output,
-                        renderer,
-                        &mut framebuffer,
-                        1.0,
-                        0,
-                        [&state.space],
-                        &[],
-                        &mut damage_tracker,
-                        make_rgb(150., 154., 171., 1.0),
-                    )
-                    .unwrap();
+                    let elements = output_elements(renderer, &output, state, &mut overlay_buffers);
+                    damage_tracker
+                        .render_output(
+                            renderer,
+                            &mut framebuffer,
+                            0,
+                            &elements,
+                            make_rgb(150., 154., 171., 1.0),
+                        )
+                        .unwrap();
                 }
 
                 winit_backend
@@ -136,6 +162,268 @@ pub fn init_winit(event_loop: &mut EventLoop<ProjectWC>, state: &mut ProjectWC)
     Ok(())
 }
 
+/// Everything shown on `output`, front to back.
+///
+/// This follows smithay's space rendering, except that Top layer surfaces
+/// such as bars are left out while a fullscreen window has focus.
+fn output_elements(
+    renderer: &mut GlesRenderer,
+    output: &Output,
+    state: &ProjectWC,
+    overlay_buffers: &mut OverlayBuffers,
+) -> Vec<OutputRenderElements<GlesRenderer>> {
+    let output_geo = state.space.output_geometry(output).unwrap_or_default();
+    let output_loc = output_geo.loc;
+    let scale = Scale::from(output.current_scale().fractional_scale());
+    let layer_map = layer_map_for_output(output);
+
+    let mut elements = Vec::new();
+
+    if let Some(switcher) = &state.switcher {
+        let slots = switcher.slots(output_geo);
+        let labels = switcher.labels(output_geo);
+        for ((window, slot), label) in switcher.windows.iter().zip(&slots).zip(&labels) {
+            elements.extend(label_element(
+                renderer,
+                &mut overlay_buffers.switcher_labels,
+                window,
+                Rectangle::new(label.loc - output_loc, label.size),
+                scale,
+            ));
+            elements.extend(thumbnail_elements(
+                renderer,
+                window,
+                Rectangle::new(slot.loc - output_loc, slot.size),
+                scale,
+            ));
+        }
+
+        if let (Some(slot), Some(label)) =
+            (slots.get(switcher.selected), labels.get(switcher.selected))
+        {
+            let selected = slot.merge(*label);
+            let selection = Rectangle::new(
+                selected.loc - Point::from((8, 8)),
+                selected.size + Size::from((16, 16)),
+            );
+            elements.push(solid_element(
+                &mut overlay_buffers.switcher_selection,
+                selection,
+                output_loc,
+                make_rgb(94., 129., 172., 1.0),
+                scale,
+                1.0,
+            ));
+        }
+        elements.push(solid_element(
+            &mut overlay_buffers.switcher_panel,
+            switcher.panel(output_geo),
+            output_loc,
+            make_rgb(46., 52., 64., 1.0),
+            scale,
+            0.9,
+        ));
+    }
+
+    if let Some(rect) = state.drop_preview {
+        elements.push(solid_element(
+            &mut overlay_buffers.drop_preview,
+            rect,
+            output_loc,
+            make_rgb(94., 129., 172., 1.0),
+            scale,
+            0.4,
+        ));
+    }
+
+    elements.extend(layer_elements(renderer, &layer_map, Layer::Overlay, scale));
+    if !state.is_fullscreen_focused() {
+        elements.extend(layer_elements(renderer, &layer_map, Layer::Top, scale));
+    }
+
+    let windows: Vec<_> = state.space.elements_for_output(output).collect();
+    for window in windows.into_iter().rev() {
+        let Some(location) = state.space.element_location(window) else {
+            continue;
+        };
+        let geometry = Rectangle::new(location, window.geometry().size);
+        let location =
+            (location - window.geometry().loc - output_loc).to_physical_precise_round(scale);
+        elements.extend(
+            window.render_elements::<OutputRenderElements<GlesRenderer>>(
+                renderer, location, scale, 1.0,
+            ),
+        );
+
+        if let Some(color) = state.border_color(window) {
+            let buffers = overlay_buffers.borders.entry(window.clone()).or_default();
+            let sides = border_rects(geometry, state.config.border.width as i32);
+            for (buffer, side) in buffers.iter_mut().zip(sides) {
+                elements.push(solid_element(buffer, side, output_loc, color, scale, 1.0));
+            }
+        }
+    }
+    overlay_buffers
+        .borders
+        .retain(|window, _| state.client(window).is_some());
+    overlay_buffers.switcher_labels.retain(|window, _| {
+        state
+            .switcher
+            .as_ref()
+            .is_some_and(|switcher| switcher.windows.contains(window))
+    });
+
+    elements.extend(layer_elements(renderer, &layer_map, Layer::Bottom, scale));
+    elements.extend(layer_elements(
+        renderer,
+        &layer_map,
+        Layer::Background,
+        scale,
+    ));
+
+    elements
+}
+
+/// The strips a border of `width` takes up around `geometry`, in the order
+/// top, bottom, left, right.
+fn border_rects(geometry: Rectangle<i32, Logical>, width: i32) -> [Rectangle<i32, Logical>; 4] {
+    let (loc, size) = (geometry.loc, geometry.size);
+    [
+        Rectangle::new(
+            (loc.x - width, loc.y - width).into(),
+            (size.w + 2 * width, width).into(),
+        ),
+        Rectangle::new(
+            (loc.x - width, loc.y + size.h).into(),
+            (size.w + 2 * width, width).into(),
+        ),
+        Rectangle::new((loc.x - width, loc.y).into(), (width, size.h).into()),
+        Rectangle::new((loc.x + size.w, loc.y).into(), (width, size.h).into()),
+    ]
+}
+
+fn solid_element(
+    buffer: &mut SolidColorBuffer,
+    rect: Rectangle<i32, Logical>,
+    output_loc: Point<i32, Logical>,
+    color: [f32; 4],
+    scale: Scale<f64>,
+    alpha: f32,
+) -> OutputRenderElements<GlesRenderer> {
+    buffer.update(rect.size, color);
+    OutputRenderElements::Solid(SolidColorRenderElement::from_buffer(
+        buffer,
+        (rect.loc - output_loc).to_physical_precise_round(scale),
+        scale,
+        alpha,
+        Kind::Unspecified,
+    ))
+}
+
+/// `window` shrunk to fit into `slot`, given relative to the output.
+fn thumbnail_elements(
+    renderer: &mut GlesRenderer,
+    window: &Window,
+    slot: Rectangle<i32, Logical>,
+    scale: Scale<f64>,
+) -> Vec<OutputRenderElements<GlesRenderer>> {
+    let geometry = window.geometry();
+    if geometry.size.w <= 0 || geometry.size.h <= 0 {
+        return Vec::new();
+    }
+
+    let factor = f64::min(
+        slot.size.w as f64 / geometry.size.w as f64,
+        slot.size.h as f64 / geometry.size.h as f64,
+    )
+    .min(1.0);
+    let size = geometry.size.to_f64().upscale(factor).to_i32_round::<i32>();
+    // Centered in the slot, scaled towards its own top left corner
+    let origin = slot.loc + Point::from(((slot.size.w - size.w) / 2, (slot.size.h - size.h) / 2));
+    let origin: Point<i32, Physical> = origin.to_physical_precise_round(scale);
+    let location = origin - geometry.loc.to_physical_precise_round(scale);
+
+    window
+        .render_elements::<WaylandSurfaceRenderElement<GlesRenderer>>(
+            renderer, location, scale, 1.0,
+        )
+        .into_iter()
+        .map(|element| {
+            OutputRenderElements::Thumbnail(RescaleRenderElement::from_element(
+                element,
+                origin,
+                Scale::from(factor),
+            ))
+        })
+        .collect()
+}
+
+/// `window`'s title over its app id, drawn into `area` given relative to
+/// the output.
+fn label_element(
+    renderer: &mut GlesRenderer,
+    labels: &mut HashMap<Window, Label>,
+    window: &Window,
+    area: Rectangle<i32, Logical>,
+    scale: Scale<f64>,
+) -> Option<OutputRenderElements<GlesRenderer>> {
+    let (title, app_id) = window.toplevel().map(title_and_app_id).unwrap_or_default();
+    let lines = [
+        (title.as_deref().unwrap_or_default(), [236, 239, 244, 255]),
+        (app_id.as_deref().unwrap_or_default(), [136, 192, 208, 255]),
+    ];
+
+    let label = labels
+        .entry(window.clone())
+        .and_modify(|label| {
+            if !label.shows(&lines, area.size.w) {
+                *label = Label::new(&lines, area.size.w);
+            }
+        })
+        .or_insert_with(|| Label::new(&lines, area.size.w));
+
+    match MemoryRenderBufferRenderElement::from_buffer(
+        renderer,
+        area.loc.to_physical_precise_round(scale).to_f64(),
+        label.buffer(),
+        None,
+        None,
+        None,
+        Kind::Unspecified,
+    ) {
+        Ok(element) => Some(OutputRenderElements::Label(element)),
+        Err(err) => {
+            tracing::warn!("failed to draw switcher label: {err:?}");
+            None
+        }
+    }
+}
+
+fn layer_elements(
+    renderer: &mut GlesRenderer,
+    layer_map: &LayerMap,
+    layer: Layer,
+    scale: Scale<f64>,
+) -> Vec<OutputRenderElements<GlesRenderer>> {
+    let surfaces: Vec<_> = layer_map.layers_on(layer).collect();
+    surfaces
+        .into_iter()
+        .rev()
+        .flat_map(|surface| {
+            let location = layer_map
+                .layer_geometry(surface)
+                .map(|geo| geo.loc)
+                .unwrap_or_default();
+            surface.render_elements::<OutputRenderElements<GlesRenderer>>(
+                renderer,
+                location.to_physical_precise_round(scale),
+                scale,
+                1.0,
+            )
+        })
+        .collect()
+}
+
 fn render_screencopy(
     renderer: &mut GlesRenderer,
     target: &GlesTarget<'_>,
@@ -0,0 +1,81 @@
+use smithay::{
+    desktop::Window,
+    utils::{Logical, Point, Rectangle},
+};
+
+/// A managed toplevel window and the compositor-side state attached to it.
+pub struct Client {
+    pub window: Window,
+    /// Bitmask of the tags this window is on.
+    pub tags: u32,
+    /// Size weight relative to the other windows in its area, like dwm's
+    /// cfacts.
+    pub cfact: f32,
+    /// Floating windows are left out of the layout and keep their own
+    /// geometry, stacked above the tiled ones.
+    pub floating: bool,
+    /// Where a floating window sits while it isn't mapped, `None` until it
+    /// has been placed for the first time.
+    pub float_location: Option<Point<i32, Logical>>,
+    /// Fullscreen windows cover the whole output, above everything else.
+    pub fullscreen: bool,
+    /// Maximized windows cover the output minus panels' exclusive zones.
+    pub maximized: bool,
+    /// Where the window was before going fullscreen or maximized.
+    pub saved_geometry: Option<Rectangle<i32, Logical>>,
+    /// Set when the window asks for attention while unfocused, cleared once
+    /// it gets focus.
+    pub urgent: bool,
+    /// Set by the first commit. Until then the window's parent and size
+    /// hints are unknown, so it is left out of the layout.
+    pub committed: bool,
+}
+
+impl Client {
+    pub fn new(window: Window, tags: u32) -> Self {
+        Self {
+            window,
+            tags,
+            cfact: 1.0,
+            floating: false,
+            float_location: None,
+            fullscreen: false,
+            maximized: false,
+            saved_geometry: None,
+            urgent: false,
+            committed: false,
+        }
+    }
+
+    pub fn set_cfact(&mut self, cfact: f32) {
+        self.cfact = cfact.clamp(0.25, 4.0);
+    }
+
+    /// Whether the layout decides where this window goes.
+    pub fn is_tiled(&self) -> bool {
+        !self.floating && !self.is_stretched()
+    }
+
+    /// Whether the window is fullscreen or maximized, which overrides both
+    /// the layout and its floating geometry.
+    pub fn is_stretched(&self) -> bool {
+        self.fullscreen || self.maximized
+    }
+
+    /// Stacking group, windows in higher groups are shown above lower ones.
+    pub fn stacking_level(&self) -> u8 {
+        if self.fullscreen {
+            2
+        } else if self.floating || self.maximized {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Whether the window is shown when the tags in `tagset` are viewed.
+    /// Windows that haven't committed yet aren't shown anywhere.
+    pub fn is_visible_on(&self, tagset: u32) -> bool {
+        self.committed && self.tags & tagset != 0
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer, de::Error as _};
+
+use crate::{
+    CompositorError, Result,
+    keybindings::{Keybindings, MouseBindings},
+    layout::{GapConfig, LayoutType},
+    rules::WindowRule,
+};
+
+/// Everything that can be tuned from `$XDG_CONFIG_HOME/projectwc/config.toml`.
+///
+/// Missing sections and keys fall back to their defaults, unknown keys are
+/// rejected so typos don't go unnoticed.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub layout: LayoutConfig,
+    pub gaps: GapConfig,
+    pub keybindings: Keybindings,
+    pub mouse: MouseBindings,
+    pub focus: FocusConfig,
+    pub border: BorderConfig,
+    pub rules: Vec<WindowRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    pub default: LayoutType,
+    #[serde(deserialize_with = "deserialize_master_factor")]
+    pub master_factor: f32,
+    #[serde(deserialize_with = "deserialize_num_master")]
+    pub num_master: i32,
+    pub smartgaps: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            default: LayoutType::Tiling,
+            master_factor: 0.55,
+            num_master: 1,
+            smartgaps: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FocusConfig {
+    /// Whether `focus-next` and `focus-previous` go round past the last and
+    /// first window.
+    pub wrap: bool,
+    /// Focus the window under the pointer once it has rested there for
+    /// `follow_mouse_delay` milliseconds.
+    pub follow_mouse: bool,
+    pub follow_mouse_delay: u64,
+    /// Move the pointer onto windows focused from the keyboard.
+    pub warp_pointer: bool,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            wrap: false,
+            follow_mouse: false,
+            follow_mouse_delay: 100,
+            warp_pointer: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BorderConfig {
+    /// Border width in logical pixels, 0 turns borders off.
+    pub width: u32,
+    pub focused: Color,
+    pub unfocused: Color,
+    /// For windows that asked for attention while unfocused.
+    pub urgent: Color,
+}
+
+impl Default for BorderConfig {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            focused: Color::rgb(0x5e, 0x81, 0xac),
+            unfocused: Color::rgb(0x4c, 0x56, 0x6a),
+            urgent: Color::rgb(0xbf, 0x61, 0x6a),
+        }
+    }
+}
+
+/// An RGBA colour, written in the config as `"#rrggbb"` or `"#rrggbbaa"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color(pub [f32; 4]);
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0])
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| (hex.len() == 6 || hex.len() == 8) && hex.is_ascii())
+            .ok_or_else(|| D::Error::custom(format!("invalid colour {s:?}, expected #rrggbb")))?;
+
+        let mut rgba = [1.0; 4];
+        for (channel, i) in rgba.iter_mut().zip((0..hex.len()).step_by(2)) {
+            let value = u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| D::Error::custom(format!("invalid colour {s:?}")))?;
+            *channel = value as f32 / 255.0;
+        }
+        Ok(Self(rgba))
+    }
+}
+
+impl Config {
+    /// Location of the config file, honouring `XDG_CONFIG_HOME`.
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("projectwc").join("config.toml"))
+    }
+
+    /// Loads the config file, or the defaults if there is none.
+    ///
+    /// A file that exists but can't be read or parsed is an error.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::from_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = fs::read_to_string(path).map_err(|err| {
+            CompositorError::Config(ConfigError {
+                path: path.to_path_buf(),
+                position: None,
+                message: err.to_string(),
+            })
+        })?;
+
+        Self::parse(&source).map_err(|err| {
+            let position = err.span().map(|span| line_column(&source, span.start));
+            CompositorError::Config(ConfigError {
+                path: path.to_path_buf(),
+                position,
+                message: err.message().trim_end().to_string(),
+            })
+        })
+    }
+
+    pub fn parse(source: &str) -> std::result::Result<Self, toml::de::Error> {
+        toml::from_str(source)
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    /// 1-based line and column of the offending token, if known.
+    pub position: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(
+                f,
+                "{}:{line}:{column}: {}",
+                self.path.display(),
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+fn deserialize_master_factor<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<f32, D::Error> {
+    let factor = f32::deserialize(deserializer)?;
+    if !(0.05..=0.95).contains(&factor) {
+        return Err(D::Error::custom(format!(
+            "master_factor must be between 0.05 and 0.95, got {factor}"
+        )));
+    }
+    Ok(factor)
+}
+
+fn deserialize_num_master<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<i32, D::Error> {
+    let num_master = i32::deserialize(deserializer)?;
+    if num_master < 0 {
+        return Err(D::Error::custom(format!(
+            "num_master must not be negative, got {num_master}"
+        )));
+    }
+    Ok(num_master)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_column_is_one_based() {
+        let source = "[layout]\nmaster_factor = 2.0\n";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 9), (2, 1));
+        assert_eq!(line_column(source, 25), (2, 17));
+        // Offsets past the end clamp to it
+        assert_eq!(line_column(source, 100), (3, 1));
+    }
+
+    #[test]
+    fn line_column_counts_characters() {
+        let source = "# é\nx";
+        assert_eq!(line_column(source, source.len() - 1), (2, 1));
+        assert_eq!(line_column(source, "# é".len()), (1, 4));
+    }
+
+    #[test]
+    fn config_error_points_at_the_bad_value() {
+        let path = std::env::temp_dir().join(format!("projectwc-test-{}.toml", std::process::id()));
+        fs::write(&path, "[layout]\nnum_master = 1\nmaster_factor = 2.0\n").unwrap();
+        let result = Config::from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        let Err(CompositorError::Config(err)) = result else {
+            panic!("expected a config error");
+        };
+        assert_eq!(err.position, Some((3, 17)));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}:3:17: master_factor must be between 0.05 and 0.95, got 2",
+                path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn config_error_without_position() {
+        let err = ConfigError {
+            path: PathBuf::from("/tmp/config.toml"),
+            position: None,
+            message: "permission denied".into(),
+        };
+        assert_eq!(err.to_string(), "/tmp/config.toml: permission denied");
+    }
+}
@@ -1,6 +1,8 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::config::ConfigError;
+
 #[derive(Debug)]
 pub enum CompositorError {
     Backend(String),
@@ -8,6 +10,8 @@ pub enum CompositorError {
     Socket(std::io::Error),
     EventLoop(String),
     Screencopy(String),
+    Config(ConfigError),
+    Ipc(String),
     InvalidAction,
 }
 
@@ -19,6 +23,8 @@ impl fmt::Display for CompositorError {
             Self::Socket(err) => write!(f, "wayland socket creation failed: {err}"),
             Self::EventLoop(msg) => write!(f, "event loop error: {msg}"),
             Self::Screencopy(msg) => write!(f, "screencopy failed: {msg}"),
+            Self::Config(err) => write!(f, "invalid config: {err}"),
+            Self::Ipc(msg) => write!(f, "ipc error: {msg}"),
             Self::InvalidAction => write!(f, "invalid action"),
         }
     }
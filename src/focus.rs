@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use smithay::{
+    desktop::Window,
+    input::pointer::MotionEvent,
+    reexports::{
+        calloop::timer::{TimeoutAction, Timer},
+        wayland_protocols::xdg::shell::server::xdg_toplevel,
+        wayland_server::protocol::wl_surface::WlSurface,
+    },
+    utils::SERIAL_COUNTER,
+};
+
+use crate::ProjectWC;
+
+/// Keyboard focus goes through here, so the focus history and the windows'
+/// activated state always agree with the keyboard.
+impl ProjectWC {
+    /// Focuses and raises `window`, or clears focus for `None`.
+    pub fn focus_window(&mut self, window: Option<&Window>) {
+        if let Some(window) = window {
+            let history = &mut self.active_workspace_mut().focus_history;
+            history.retain(|w| w != window);
+            history.push(window.clone());
+
+            if let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) {
+                client.urgent = false;
+            }
+        }
+
+        let surface = window
+            .and_then(|w| w.toplevel())
+            .map(|tl| tl.wl_surface().clone());
+        self.set_keyboard_focus(surface, window);
+
+        if let Some(window) = window {
+            self.space.raise_element(window, false);
+            self.restack();
+        }
+    }
+
+    /// Focuses `window` for a keyboard action, taking the pointer along if
+    /// the config asks for that.
+    pub fn focus_window_by_keyboard(&mut self, window: &Window) {
+        self.focus_window(Some(window));
+        if self.config.focus.warp_pointer {
+            self.warp_pointer_to(window);
+        }
+    }
+
+    /// Focuses a layer surface such as a launcher, leaving no window active.
+    pub fn focus_layer(&mut self, surface: &WlSurface) {
+        self.set_keyboard_focus(Some(surface.clone()), None);
+    }
+
+    /// Focuses the most recently focused window still shown, falling back
+    /// to the first one in tiling order.
+    pub fn refocus(&mut self) {
+        let visible = self.visible_windows();
+        let window = self
+            .active_workspace()
+            .focus_history
+            .iter()
+            .rev()
+            .find(|window| visible.contains(window))
+            .or(visible.first())
+            .cloned();
+        self.focus_window(window.as_ref());
+    }
+
+    /// Moves focus on if it is on a window that is no longer shown. Layer
+    /// surfaces keep their focus.
+    pub fn refocus_if_hidden(&mut self) {
+        let keyboard = self.seat.get_keyboard().unwrap();
+        let visible = self.visible_windows();
+        let needs_focus = match keyboard.current_focus() {
+            None => true,
+            Some(surface) => self
+                .window_for_surface(&surface)
+                .is_some_and(|window| !visible.contains(&window)),
+        };
+
+        if needs_focus {
+            self.refocus();
+        }
+    }
+
+    /// Schedules focusing the window under the pointer, if focus follows the
+    /// mouse. Moving on to another window before the delay is up cancels it.
+    pub fn update_sloppy_focus(&mut self) {
+        if !self.config.focus.follow_mouse {
+            return;
+        }
+
+        let focused = self.focused_window();
+        let target = self
+            .window_under_pointer()
+            .map(|(window, _)| window)
+            .filter(|window| Some(window) != focused.as_ref() && self.sloppy_focus_allowed());
+        if self.sloppy_focus.as_ref().map(|(window, _)| window) == target.as_ref() {
+            return;
+        }
+
+        self.cancel_sloppy_focus();
+        let Some(window) = target else {
+            return;
+        };
+
+        let delay = Duration::from_millis(self.config.focus.follow_mouse_delay);
+        let pending = window.clone();
+        let timer =
+            self.loop_handle
+                .insert_source(Timer::from_duration(delay), move |_, _, state| {
+                    state.sloppy_focus = None;
+                    // The layout or the pointer may have moved on meanwhile
+                    let still_under_pointer = state
+                        .window_under_pointer()
+                        .is_some_and(|(window, _)| window == pending);
+                    if still_under_pointer
+                        && state.visible_windows().contains(&pending)
+                        && state.sloppy_focus_allowed()
+                    {
+                        state.focus_window(Some(&pending));
+                    }
+                    TimeoutAction::Drop
+                });
+        match timer {
+            Ok(token) => self.sloppy_focus = Some((window, token)),
+            Err(err) => tracing::warn!("failed to schedule focus change: {err}"),
+        }
+    }
+
+    /// Drops a pending focus change from `update_sloppy_focus`.
+    pub fn cancel_sloppy_focus(&mut self) {
+        if let Some((_, token)) = self.sloppy_focus.take() {
+            self.loop_handle.remove(token);
+        }
+    }
+
+    /// Grabs and exclusive layer surfaces keep focus where it is.
+    fn sloppy_focus_allowed(&self) -> bool {
+        let keyboard = self.seat.get_keyboard().unwrap();
+        !self.pointer().is_grabbed() && !keyboard.is_grabbed() && self.exclusive_layer().is_none()
+    }
+
+    /// Moves the pointer to the middle of `window`, unless it is on the
+    /// window already.
+    fn warp_pointer_to(&mut self, window: &Window) {
+        let Some(geometry) = self.space.element_geometry(window) else {
+            return;
+        };
+        if geometry.to_f64().contains(self.pointer_location) {
+            return;
+        }
+
+        self.pointer_location =
+            geometry.to_f64().loc + geometry.size.to_f64().downscale(2.0).to_point();
+        let pointer = self.pointer();
+        let under = self.surface_under_pointer();
+        pointer.motion(
+            self,
+            under,
+            &MotionEvent {
+                location: self.pointer_location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: self.start_time.elapsed().as_millis() as u32,
+            },
+        );
+        pointer.frame(self);
+    }
+
+    /// Drops a closed window from every workspace's focus history.
+    pub fn forget_focus(&mut self, window: &Window) {
+        for workspace in &mut self.workspaces {
+            workspace.focus_history.retain(|w| w != window);
+        }
+
+        if let Some(switcher) = &mut self.switcher
+            && let Some(idx) = switcher.windows.iter().position(|w| w == window)
+        {
+            switcher.windows.remove(idx);
+            if switcher.selected > idx || switcher.selected == switcher.windows.len() {
+                switcher.selected = switcher.selected.saturating_sub(1);
+            }
+
+            // Nothing left to switch between
+            if switcher.windows.len() < 2 {
+                self.switcher = None;
+                let keyboard = self.seat.get_keyboard().unwrap();
+                keyboard.unset_grab(self);
+            }
+        }
+    }
+
+    fn set_keyboard_focus(&mut self, surface: Option<WlSurface>, active: Option<&Window>) {
+        for client in &self.clients {
+            let Some(toplevel) = client.window.toplevel() else {
+                continue;
+            };
+
+            toplevel.with_pending_state(|state| {
+                if Some(&client.window) == active {
+                    state.states.set(xdg_toplevel::State::Activated);
+                } else {
+                    state.states.unset(xdg_toplevel::State::Activated);
+                }
+            });
+            // The initial configure waits for the first commit
+            if toplevel.is_initial_configure_sent() {
+                toplevel.send_pending_configure();
+            }
+        }
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        let serial = SERIAL_COUNTER.next_serial();
+        keyboard.set_focus(self, surface, serial);
+    }
+}
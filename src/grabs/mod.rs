@@ -1,2 +1,4 @@
 pub mod move_grab;
 pub mod resize_grab;
+pub mod switcher_grab;
+pub mod tiled_resize_grab;
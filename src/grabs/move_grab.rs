@@ -12,10 +12,44 @@ use smithay::{
 
 use crate::ProjectWC;
 
+/// How far the pointer has to travel before a click becomes a drag, so a
+/// shaky click doesn't float or swap a tiled window.
+const DRAG_THRESHOLD: f64 = 6.0;
+
 pub struct MoveGrab {
     pub start_data: PointerGrabStartData<ProjectWC>,
     pub window: Window,
     pub initial_window_location: Point<i32, Logical>,
+    /// Tiled windows float when dropped, or swap with the tile they are
+    /// dropped onto.
+    tiled: bool,
+    moved: bool,
+    drop_target: Option<Window>,
+}
+
+impl MoveGrab {
+    pub fn start(
+        start_data: PointerGrabStartData<ProjectWC>,
+        window: Window,
+        state: &ProjectWC,
+    ) -> Option<Self> {
+        // Fullscreen and maximized windows stay put
+        if state.is_stretched(&window) {
+            return None;
+        }
+
+        let initial_window_location = state.space.element_location(&window)?;
+        let tiled = state.is_tiled(&window);
+
+        Some(Self {
+            start_data,
+            window,
+            initial_window_location,
+            tiled,
+            moved: false,
+            drop_target: None,
+        })
+    }
 }
 
 impl PointerGrab<ProjectWC> for MoveGrab {
@@ -30,9 +64,22 @@ impl PointerGrab<ProjectWC> for MoveGrab {
         handle.motion(data, None, event);
 
         let delta = event.location - self.start_data.location;
+        self.moved |= delta.x.hypot(delta.y) >= DRAG_THRESHOLD;
+        if !self.moved {
+            return;
+        }
+
         let new_location = self.initial_window_location.to_f64() + delta;
         data.space
-            .map_element(self.window.clone(), new_location.to_i32_round(), true);
+            .map_element(self.window.clone(), new_location.to_i32_round(), false);
+
+        if self.tiled {
+            self.drop_target = data.tiled_window_at(event.location, &self.window);
+            data.drop_preview = self
+                .drop_target
+                .as_ref()
+                .and_then(|target| data.space.element_geometry(target));
+        }
     }
 
     fn relative_motion(
@@ -53,12 +100,8 @@ impl PointerGrab<ProjectWC> for MoveGrab {
     ) {
         handle.button(data, event);
 
-        // The button is a button code as defined in the
-        // Linux kernel's linux/input-event-codes.h header file, e.g. BTN_LEFT.
-        const BTN_LEFT: u32 = 0x110;
-
-        if !handle.current_pressed().contains(&BTN_LEFT) {
-            // No more buttons are pressed, release the grab.
+        if !handle.current_pressed().contains(&self.start_data.button) {
+            // The button that started the grab was released
             handle.unset_grab(self, data, event.serial, event.time, true);
         }
     }
@@ -153,6 +196,17 @@ impl PointerGrab<ProjectWC> for MoveGrab {
     }
 
     fn unset(&mut self, data: &mut ProjectWC) {
-        data.apply_layout().ok();
+        data.drop_preview = None;
+
+        if !self.tiled || !self.moved {
+            data.apply_layout().ok();
+            return;
+        }
+
+        match self.drop_target.take() {
+            Some(target) => data.swap_windows(&self.window, &target),
+            // Dropped anywhere else, the window stays where it was let go
+            None => data.set_floating(&self.window, true),
+        }
     }
 }
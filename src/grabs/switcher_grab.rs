@@ -0,0 +1,71 @@
+use smithay::{
+    backend::input::KeyState,
+    input::{
+        SeatHandler,
+        keyboard::{
+            GrabStartData as KeyboardGrabStartData, KeyboardGrab, KeyboardInnerHandle, Keycode,
+            ModifiersState,
+        },
+    },
+    utils::Serial,
+};
+
+use crate::ProjectWC;
+
+/// Holds the keyboard while the window switcher is open.
+///
+/// Further presses of the switcher binding still reach the keybindings and
+/// step through the windows. Other keys are swallowed, and letting go of
+/// the modifiers focuses the selected window.
+pub struct SwitcherGrab {
+    start_data: KeyboardGrabStartData<ProjectWC>,
+}
+
+impl SwitcherGrab {
+    pub fn new(start_data: KeyboardGrabStartData<ProjectWC>) -> Self {
+        Self { start_data }
+    }
+}
+
+impl KeyboardGrab<ProjectWC> for SwitcherGrab {
+    fn input(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut KeyboardInnerHandle<'_, ProjectWC>,
+        keycode: Keycode,
+        state: KeyState,
+        modifiers: Option<ModifiersState>,
+        serial: Serial,
+        time: u32,
+    ) {
+        let Some(modifiers) = modifiers else {
+            return;
+        };
+
+        // Keep the focused client's modifier state right
+        handle.input(data, keycode, state, Some(modifiers), serial, time);
+
+        if !modifiers.alt && !modifiers.ctrl && !modifiers.logo {
+            handle.unset_grab(self, data, serial, true);
+        }
+    }
+
+    fn set_focus(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut KeyboardInnerHandle<'_, ProjectWC>,
+        focus: Option<<ProjectWC as SeatHandler>::KeyboardFocus>,
+        serial: Serial,
+    ) {
+        handle.set_focus(data, focus, serial);
+    }
+
+    fn start_data(&self) -> &KeyboardGrabStartData<ProjectWC> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut ProjectWC) {
+        // The keyboard is still locked here, so focus changes once it is not
+        data.loop_handle.insert_idle(|state| state.finish_switch());
+    }
+}
@@ -0,0 +1,273 @@
+use smithay::{
+    desktop::{Window, layer_map_for_output},
+    input::pointer::{
+        AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+        GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent,
+        GestureSwipeEndEvent, GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData,
+        MotionEvent, PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+    },
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point},
+};
+
+use crate::{ProjectWC, grabs::resize_grab::ResizeEdge, layout::LayoutType};
+
+/// Resizes a tiled window by changing the layout instead of the window.
+///
+/// In the tiling layout, moving a left or right edge shifts the master/stack
+/// border and moving a top or bottom edge changes the window's weight within
+/// its column. The bottom stack layout works the same with the axes swapped.
+/// Other layouts have no such border to move.
+pub struct TiledResizeGrab {
+    start_data: PointerGrabStartData<ProjectWC>,
+    window: Window,
+    edges: ResizeEdge,
+    /// Master and stack are laid out in rows rather than columns.
+    rows: bool,
+
+    initial_master_factor: f32,
+    initial_cfact: f32,
+    /// The window's extent along its column or row.
+    initial_size: i32,
+    /// Summed weight of the other windows in the same column or row.
+    column_rest: f32,
+    /// Width, or height for rows, the master factor is a fraction of.
+    tiling_size: i32,
+}
+
+impl TiledResizeGrab {
+    pub fn start(
+        start_data: PointerGrabStartData<ProjectWC>,
+        window: Window,
+        edges: ResizeEdge,
+        state: &ProjectWC,
+    ) -> Option<Self> {
+        let layout_type = state.active_workspace().layout_type;
+        if !Self::supports(layout_type) {
+            return None;
+        }
+        let rows = layout_type == LayoutType::BottomStack;
+
+        let tiled: Vec<_> = state
+            .clients
+            .iter()
+            .filter(|client| client.is_visible_on(state.tagset) && client.is_tiled())
+            .collect();
+        let position = tiled.iter().position(|client| client.window == window)?;
+
+        let num_master = state.active_workspace().num_master.max(0) as usize;
+        let in_master = |i: usize| i < num_master;
+        let column_rest = tiled
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != position && in_master(i) == in_master(position))
+            .map(|(_, client)| client.cfact)
+            .sum();
+
+        let output = state.space.outputs().next()?;
+        let tiling_area = layer_map_for_output(output).non_exclusive_zone().size;
+        let window_size = state.space.element_geometry(&window)?.size;
+        let (tiling_size, initial_size) = if rows {
+            (tiling_area.h, window_size.w)
+        } else {
+            (tiling_area.w, window_size.h)
+        };
+
+        Some(Self {
+            start_data,
+            edges,
+            rows,
+            initial_master_factor: state.active_workspace().master_factor,
+            initial_cfact: tiled[position].cfact,
+            initial_size,
+            column_rest,
+            tiling_size,
+            window,
+        })
+    }
+}
+
+impl TiledResizeGrab {
+    /// Whether tiles in `layout_type` can be resized by dragging.
+    pub fn supports(layout_type: LayoutType) -> bool {
+        matches!(layout_type, LayoutType::Tiling | LayoutType::BottomStack)
+    }
+}
+
+impl PointerGrab<ProjectWC> for TiledResizeGrab {
+    fn motion(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        // While the grab is active, no client has pointer focus
+        handle.motion(data, None, event);
+
+        let delta = event.location - self.start_data.location;
+        // Motion across the master/stack border, and along the window's
+        // column or row
+        let (across, mut along, border_edges, column_edges, start_edge) = if self.rows {
+            (
+                delta.y,
+                delta.x,
+                ResizeEdge::TOP | ResizeEdge::BOTTOM,
+                ResizeEdge::LEFT | ResizeEdge::RIGHT,
+                ResizeEdge::LEFT,
+            )
+        } else {
+            (
+                delta.x,
+                delta.y,
+                ResizeEdge::LEFT | ResizeEdge::RIGHT,
+                ResizeEdge::TOP | ResizeEdge::BOTTOM,
+                ResizeEdge::TOP,
+            )
+        };
+
+        if self.edges.intersects(border_edges) && self.tiling_size > 0 {
+            // Whichever edge is dragged, moving away from the master area
+            // grows it
+            let factor = self.initial_master_factor + (across / self.tiling_size as f64) as f32;
+            data.active_workspace_mut().set_master_factor(factor);
+        }
+
+        if self.edges.intersects(column_edges) && self.column_rest > 0.0 && self.initial_size > 0 {
+            if self.edges.intersects(start_edge) {
+                along = -along;
+            }
+
+            // Pick the weight that gives the window its dragged-to share of
+            // the column, the other windows keep their relative sizes.
+            let initial_cfact = f64::from(self.initial_cfact);
+            let column_rest = f64::from(self.column_rest);
+            let column_size =
+                self.initial_size as f64 * (initial_cfact + column_rest) / initial_cfact;
+            let share = ((self.initial_size as f64 + along) / column_size).clamp(0.05, 0.95);
+
+            if let Some(client) = data.clients.iter_mut().find(|c| c.window == self.window) {
+                client.set_cfact((column_rest * share / (1.0 - share)) as f32);
+            }
+        }
+
+        data.apply_layout().ok();
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+
+        if !handle.current_pressed().contains(&self.start_data.button) {
+            // The button that started the grab was released
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details)
+    }
+
+    fn frame(&mut self, data: &mut ProjectWC, handle: &mut PointerInnerHandle<'_, ProjectWC>) {
+        handle.frame(data);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event)
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event)
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event)
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event)
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event)
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event)
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event)
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut ProjectWC,
+        handle: &mut PointerInnerHandle<'_, ProjectWC>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event)
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<ProjectWC> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut ProjectWC) {
+        data.apply_layout().ok();
+    }
+}
@@ -44,9 +44,12 @@ impl CompositorHandler for ProjectWC {
             }
         }
 
-        xdg_shell::handle_commit(&mut self.popups, &self.space, surface);
+        self.handle_toplevel_commit(surface);
+        xdg_shell::handle_commit(&mut self.popups, surface);
         resize_grab::handle_commit(&mut self.space, surface);
-        layer_shell::handle_commit(&mut self.space, surface);
+        if layer_shell::handle_commit(&mut self.space, surface) {
+            self.apply_layout().ok();
+        }
     }
 }
 
@@ -51,6 +51,14 @@ impl WlrLayerShellHandler for ProjectWC {
             layer.map(|layer| (map, layer))
         }) {
             map.unmap_layer(&layer);
+            drop(map);
+            // Tiled windows can take over the space the surface reserved
+            self.apply_layout().ok();
+
+            let keyboard = self.seat.get_keyboard().unwrap();
+            if keyboard.current_focus().as_ref() == Some(layer.wl_surface()) {
+                self.refocus();
+            }
         }
     }
 
@@ -61,13 +69,17 @@ impl WlrLayerShellHandler for ProjectWC {
 delegate_layer_shell!(ProjectWC);
 
 /// Should be called on `WlSurface::commit`
-pub fn handle_commit(space: &mut Space<Window>, surface: &WlSurface) {
+///
+/// Returns whether the area left free by layer surfaces changed, in which
+/// case the windows need to be laid out again.
+pub fn handle_commit(space: &mut Space<Window>, surface: &WlSurface) -> bool {
     for output in space.outputs() {
         let mut layer_map = layer_map_for_output(output);
         if let Some(layer) = layer_map
             .layer_for_surface(surface, WindowSurfaceType::TOPLEVEL)
             .cloned()
         {
+            let zone = layer_map.non_exclusive_zone();
             layer_map.arrange();
 
             let initial_configure_sent = compositor::with_states(surface, |states| {
@@ -80,7 +92,9 @@ pub fn handle_commit(space: &mut Space<Window>, surface: &WlSurface) {
             if !initial_configure_sent {
                 layer.layer_surface().send_configure();
             }
-            break;
+            return layer_map.non_exclusive_zone() != zone;
         }
     }
+
+    false
 }
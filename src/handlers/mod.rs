@@ -1,5 +1,7 @@
 mod compositor;
 mod layer_shell;
+mod xdg_activation;
+mod xdg_decoration;
 mod xdg_shell;
 
 use smithay::{
@@ -0,0 +1,35 @@
+use smithay::{
+    delegate_xdg_activation,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    wayland::xdg_activation::{
+        XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
+    },
+};
+
+use crate::ProjectWC;
+
+impl XdgActivationHandler for ProjectWC {
+    fn activation_state(&mut self) -> &mut XdgActivationState {
+        &mut self.xdg_activation_state
+    }
+
+    fn request_activation(
+        &mut self,
+        _token: XdgActivationToken,
+        _token_data: XdgActivationTokenData,
+        surface: WlSurface,
+    ) {
+        let Some(window) = self.window_for_surface(&surface) else {
+            return;
+        };
+        if self.focused_window().as_ref() == Some(&window) {
+            return;
+        }
+
+        // Like dwm, mark the window instead of letting it steal focus
+        if let Some(client) = self.clients.iter_mut().find(|c| c.window == window) {
+            client.urgent = true;
+        }
+    }
+}
+delegate_xdg_activation!(ProjectWC);
@@ -0,0 +1,47 @@
+use smithay::{
+    delegate_xdg_decoration,
+    reexports::wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode,
+    wayland::shell::xdg::{ToplevelSurface, decoration::XdgDecorationHandler},
+};
+
+use crate::{
+    ProjectWC,
+    rules::{DecorationMode, rule_setting},
+};
+
+/// Windows get server-side decorations, which here means just the border,
+/// unless a window rule says otherwise. What the client asks for is ignored.
+impl XdgDecorationHandler for ProjectWC {
+    fn new_decoration(&mut self, toplevel: ToplevelSurface) {
+        self.update_decoration_mode(&toplevel);
+    }
+
+    fn request_mode(&mut self, toplevel: ToplevelSurface, _mode: Mode) {
+        self.update_decoration_mode(&toplevel);
+    }
+
+    fn unset_mode(&mut self, toplevel: ToplevelSurface) {
+        self.update_decoration_mode(&toplevel);
+    }
+}
+delegate_xdg_decoration!(ProjectWC);
+
+impl ProjectWC {
+    /// Sets the decoration mode the window rules pick for `toplevel`.
+    ///
+    /// The app id is usually only known by the first commit, so this runs
+    /// again before the initial configure.
+    pub fn update_decoration_mode(&mut self, toplevel: &ToplevelSurface) {
+        let mode = match rule_setting(&self.config.rules, toplevel, |rule| rule.decorations) {
+            Some(DecorationMode::Client) => Mode::ClientSide,
+            Some(DecorationMode::Server) | None => Mode::ServerSide,
+        };
+
+        toplevel.with_pending_state(|state| {
+            state.decoration_mode = Some(mode);
+        });
+        if toplevel.is_initial_configure_sent() {
+            toplevel.send_pending_configure();
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use smithay::{
     delegate_xdg_shell,
     desktop::{
-        PopupKind, PopupManager, Space, Window, find_popup_root_surface, get_popup_toplevel_coords,
+        PopupKind, PopupManager, Window, find_popup_root_surface, get_popup_toplevel_coords,
     },
     input::{
         Seat,
@@ -11,22 +11,27 @@ use smithay::{
         wayland_protocols::xdg::shell::server::xdg_toplevel,
         wayland_server::{
             Resource,
-            protocol::{wl_seat, wl_surface::WlSurface},
+            protocol::{wl_output::WlOutput, wl_seat, wl_surface::WlSurface},
         },
     },
-    utils::{Rectangle, Serial},
+    utils::{Point, Rectangle, Serial},
     wayland::{
         compositor,
         shell::xdg::{
-            PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
-            XdgToplevelSurfaceData,
+            PopupSurface, PositionerState, SurfaceCachedState, ToplevelSurface, XdgShellHandler,
+            XdgShellState,
         },
     },
 };
 
 use crate::{
     ProjectWC,
-    grabs::{move_grab::MoveGrab, resize_grab::ResizeSurfaceGrab},
+    client::Client,
+    grabs::{
+        move_grab::MoveGrab,
+        resize_grab::{ResizeEdge, ResizeSurfaceGrab},
+        tiled_resize_grab::TiledResizeGrab,
+    },
 };
 
 impl XdgShellHandler for ProjectWC {
@@ -35,10 +40,10 @@ impl XdgShellHandler for ProjectWC {
     }
 
     fn new_toplevel(&mut self, surface: ToplevelSurface) {
-        let window = Window::new_wayland_window(surface.clone());
-        self.space.map_element(window, (0, 0), false);
-        self.apply_layout().ok();
-        surface.send_configure();
+        // Mapped once the initial state is committed, see
+        // `ProjectWC::handle_toplevel_commit`
+        let window = Window::new_wayland_window(surface);
+        self.clients.push(Client::new(window, self.tagset));
     }
 
     fn new_popup(&mut self, surface: PopupSurface, _positioner: PositionerState) {
@@ -76,15 +81,9 @@ impl XdgShellHandler for ProjectWC {
 
             let window = self.window_for_surface(wl_surface).unwrap();
 
-            let initial_window_location = self.space.element_location(&window).unwrap();
-
-            let grab = MoveGrab {
-                start_data,
-                window,
-                initial_window_location,
-            };
-
-            pointer.set_grab(self, grab, serial, Focus::Clear);
+            if let Some(grab) = MoveGrab::start(start_data, window, self) {
+                pointer.set_grab(self, grab, serial, Focus::Clear);
+            }
         }
     }
 
@@ -99,54 +98,32 @@ impl XdgShellHandler for ProjectWC {
         let wl_surface = surface.wl_surface();
 
         if let Some(start_data) = check_grab(&seat, wl_surface, serial) {
-            let pointer = seat.get_pointer().unwrap();
-
             let window = self.window_for_surface(wl_surface).unwrap();
-
-            let initial_window_location = self.space.element_location(&window).unwrap();
-            let initial_window_size = window.geometry().size;
-
-            surface.with_pending_state(|state| {
-                state.states.set(xdg_toplevel::State::Resizing);
-            });
-
-            surface.send_pending_configure();
-
-            let grab = ResizeSurfaceGrab::start(
-                start_data,
-                window,
-                edges.into(),
-                Rectangle::new(initial_window_location, initial_window_size),
-            );
-
-            pointer.set_grab(self, grab, serial, Focus::Clear);
+            self.start_resize(start_data, window, edges.into(), serial);
         }
     }
 
     fn maximize_request(&mut self, surface: ToplevelSurface) {
-        let window = self.window_for_surface(surface.wl_surface()).unwrap();
-        let output = self.space.outputs().next().unwrap();
-        let geometry = self.space.output_geometry(output).unwrap();
-
-        surface.with_pending_state(|state| {
-            state.states.set(xdg_toplevel::State::Maximized);
-            state.size = Some(geometry.size);
-        });
-        self.space.map_element(window, geometry.loc, true);
-
-        if surface.is_initial_configure_sent() {
-            surface.send_configure();
+        if let Some(window) = self.window_for_surface(surface.wl_surface()) {
+            self.set_maximized(&window, true);
         }
     }
 
     fn unmaximize_request(&mut self, surface: ToplevelSurface) {
-        surface.with_pending_state(|state| {
-            state.states.unset(xdg_toplevel::State::Maximized);
-            state.size = None;
-        });
+        if let Some(window) = self.window_for_surface(surface.wl_surface()) {
+            self.set_maximized(&window, false);
+        }
+    }
+
+    fn fullscreen_request(&mut self, surface: ToplevelSurface, _output: Option<WlOutput>) {
+        if let Some(window) = self.window_for_surface(surface.wl_surface()) {
+            self.set_fullscreen(&window, true);
+        }
+    }
 
-        if surface.is_initial_configure_sent() {
-            surface.send_configure();
+    fn unfullscreen_request(&mut self, surface: ToplevelSurface) {
+        if let Some(window) = self.window_for_surface(surface.wl_surface()) {
+            self.set_fullscreen(&window, false);
         }
     }
 
@@ -154,8 +131,15 @@ impl XdgShellHandler for ProjectWC {
         let window = self.window_for_surface(surface.wl_surface());
 
         if let Some(window) = window {
+            let focused = self.focused_window().as_ref() == Some(&window);
+            self.clients.retain(|client| client.window != window);
             self.space.unmap_elem(&window);
+            self.forget_focus(&window);
             self.apply_layout().ok();
+
+            if focused {
+                self.refocus();
+            }
         }
     }
 }
@@ -186,28 +170,7 @@ fn check_grab(
 }
 
 /// Should be called on `WlSurface::commit`
-pub fn handle_commit(popups: &mut PopupManager, space: &Space<Window>, surface: &WlSurface) {
-    // Handle toplevel commits.
-    if let Some(window) = space
-        .elements()
-        .find(|w| w.toplevel().unwrap().wl_surface() == surface)
-        .cloned()
-    {
-        let initial_configure_sent = compositor::with_states(surface, |states| {
-            states
-                .data_map
-                .get::<XdgToplevelSurfaceData>()
-                .unwrap()
-                .lock()
-                .unwrap()
-                .initial_configure_sent
-        });
-
-        if !initial_configure_sent {
-            window.toplevel().unwrap().send_configure();
-        }
-    }
-
+pub fn handle_commit(popups: &mut PopupManager, surface: &WlSurface) {
     // Handle popup commits.
     popups.commit(surface);
     if let Some(popup) = popups.find_popup(surface) {
@@ -222,7 +185,133 @@ pub fn handle_commit(popups: &mut PopupManager, space: &Space<Window>, surface:
     }
 }
 
+/// Dialogs and fixed-size windows float, like dwm does for them.
+fn should_float(toplevel: &ToplevelSurface) -> bool {
+    if toplevel.parent().is_some() {
+        return true;
+    }
+
+    compositor::with_states(toplevel.wl_surface(), |states| {
+        let mut guard = states.cached_state.get::<SurfaceCachedState>();
+        let state = guard.current();
+        state.min_size.w > 0 && state.min_size.h > 0 && state.min_size == state.max_size
+    })
+}
+
 impl ProjectWC {
+    /// Starts an interactive resize of `window` from `edges`.
+    ///
+    /// The layout owns a tiled window's size, so tiled windows resize the
+    /// layout instead.
+    pub fn start_resize(
+        &mut self,
+        start_data: PointerGrabStartData<ProjectWC>,
+        window: Window,
+        edges: ResizeEdge,
+        serial: Serial,
+    ) {
+        let pointer = self.pointer();
+
+        if !self.can_resize(&window) {
+            return;
+        }
+
+        if self.is_tiled(&window) {
+            if let Some(grab) = TiledResizeGrab::start(start_data, window, edges, self) {
+                pointer.set_grab(self, grab, serial, Focus::Clear);
+            }
+            return;
+        }
+
+        let Some(initial_window_location) = self.space.element_location(&window) else {
+            return;
+        };
+        let initial_window_size = window.geometry().size;
+
+        let toplevel = window.toplevel().unwrap();
+        toplevel.with_pending_state(|state| {
+            state.states.set(xdg_toplevel::State::Resizing);
+        });
+        toplevel.send_pending_configure();
+
+        let grab = ResizeSurfaceGrab::start(
+            start_data,
+            window,
+            edges,
+            Rectangle::new(initial_window_location, initial_window_size),
+        );
+
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+    }
+
+    /// Whether `start_resize` would start resizing `window`. Fullscreen and
+    /// maximized windows keep their size, as do tiles in layouts without a
+    /// master/stack border.
+    pub fn can_resize(&self, window: &Window) -> bool {
+        if self.is_stretched(window) {
+            return false;
+        }
+        if self.is_tiled(window) {
+            return TiledResizeGrab::supports(self.active_workspace().layout_type);
+        }
+        self.space.element_location(window).is_some()
+    }
+
+    /// Should be called on `WlSurface::commit`
+    ///
+    /// The first commit carries the parent and size hints, so that is when a
+    /// new toplevel gets tiled or floated. Floating windows are centered once
+    /// their size is known.
+    pub fn handle_toplevel_commit(&mut self, surface: &WlSurface) {
+        let Some(idx) = self.clients.iter().position(|client| {
+            client
+                .window
+                .toplevel()
+                .is_some_and(|tl| tl.wl_surface() == surface)
+        }) else {
+            return;
+        };
+        let toplevel = self.clients[idx].window.toplevel().unwrap().clone();
+
+        if !self.clients[idx].committed {
+            self.clients[idx].committed = true;
+            self.clients[idx].floating = should_float(&toplevel);
+            self.update_decoration_mode(&toplevel);
+            self.apply_layout().ok();
+
+            // Floating windows pick their own size
+            if !toplevel.is_initial_configure_sent() {
+                toplevel.send_configure();
+            }
+            return;
+        }
+
+        let client = &mut self.clients[idx];
+        if !client.floating || client.float_location.is_some() {
+            return;
+        }
+
+        let size = client.window.geometry().size;
+        let Some(output_geo) = self
+            .space
+            .outputs()
+            .next()
+            .and_then(|output| self.space.output_geometry(output))
+        else {
+            return;
+        };
+        if size.w <= 0 || size.h <= 0 {
+            return;
+        }
+
+        let offset = Point::from((
+            (output_geo.size.w - size.w) / 2,
+            (output_geo.size.h - size.h) / 2,
+        ));
+        client.float_location = Some(output_geo.loc + offset);
+        self.restack();
+    }
+
     pub fn unconstrain_popup(&self, popup: &PopupSurface) {
         let Ok(root) = find_popup_root_surface(&PopupKind::Xdg(popup.clone())) else {
             return;
@@ -232,9 +321,18 @@ impl ProjectWC {
             return;
         };
 
-        let output = self.space.outputs().next().unwrap();
-        let output_geo = self.space.output_geometry(output).unwrap();
-        let window_geo = self.space.element_geometry(&window).unwrap();
+        // Windows on hidden tags or not yet committed aren't mapped
+        let Some(output_geo) = self
+            .space
+            .outputs()
+            .next()
+            .and_then(|output| self.space.output_geometry(output))
+        else {
+            return;
+        };
+        let Some(window_geo) = self.space.element_geometry(&window) else {
+            return;
+        };
 
         // The target geometry for the positioner should be relative to its parent's geometry, so
         // we will compute that here.
@@ -1,13 +1,15 @@
-use crate::{action::Action, grabs::move_grab::MoveGrab, state::ProjectWC};
+use crate::{
+    grabs::{move_grab::MoveGrab, resize_grab::ResizeEdge},
+    state::ProjectWC,
+};
 use smithay::{
     backend::input::{
         AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event, InputBackend, InputEvent,
-        KeyState, KeyboardKeyEvent, MouseButton, PointerAxisEvent, PointerButtonEvent,
-        PointerMotionEvent,
+        KeyState, KeyboardKeyEvent, PointerAxisEvent, PointerButtonEvent, PointerMotionEvent,
     },
-    desktop::{WindowSurfaceType, layer_map_for_output},
+    desktop::{LayerSurface, Window, WindowSurfaceType, layer_map_for_output},
     input::{
-        keyboard::{FilterResult, Keysym, ModifiersState},
+        keyboard::FilterResult,
         pointer::{
             AxisFrame, ButtonEvent, Focus, GrabStartData as PointerGrabStartData, MotionEvent,
         },
@@ -42,34 +44,14 @@ impl ProjectWC {
 
         let keyboard = self.seat.get_keyboard().expect("keyboard not initialized");
 
-        for layer in self.layer_shell_state.layer_surfaces().rev() {
-            let exclusive = compositor::with_states(layer.wl_surface(), |states| {
-                let mut guard = states.cached_state.get::<LayerSurfaceCachedState>();
-                let data = guard.current();
-
-                data.keyboard_interactivity == KeyboardInteractivity::Exclusive
-                    && (data.layer == WlrLayer::Top || data.layer == WlrLayer::Overlay)
-            });
-
-            if exclusive {
-                let surface = self.space.outputs().find_map(|output| {
-                    let map = layer_map_for_output(output);
-                    map.layers().find(|l| l.layer_surface() == &layer).cloned()
-                });
-
-                if let Some(surface) = surface {
-                    keyboard.set_focus(self, Some(surface.wl_surface().clone()), serial);
-                    keyboard.input::<(), _>(
-                        self,
-                        key_code,
-                        key_state,
-                        serial,
-                        time_msec,
-                        |_, _, _| FilterResult::Forward,
-                    );
-                    return;
-                }
+        if let Some(surface) = self.exclusive_layer() {
+            if keyboard.current_focus().as_ref() != Some(surface.wl_surface()) {
+                self.focus_layer(surface.wl_surface());
             }
+            keyboard.input::<(), _>(self, key_code, key_state, serial, time_msec, |_, _, _| {
+                FilterResult::Forward
+            });
+            return;
         }
 
         keyboard.input::<(), _>(
@@ -79,17 +61,44 @@ impl ProjectWC {
             serial,
             time_msec,
             |state, modifiers, keysym_handle| {
-                if key_state == KeyState::Pressed {
-                    let keysym = keysym_handle.modified_sym();
-                    if handle_keybinding(state, modifiers, keysym) {
-                        return FilterResult::Intercept(());
-                    }
+                if key_state == KeyState::Pressed
+                    && let Some(action) = state
+                        .config
+                        .keybindings
+                        .action_for(modifiers, &keysym_handle)
+                {
+                    tracing::debug!(?action, "Keybinding triggered");
+                    action.execute(state);
+                    return FilterResult::Intercept(());
                 }
                 FilterResult::Forward
             },
         );
     }
 
+    /// The topmost layer surface, such as a launcher or lock screen, that
+    /// takes all keyboard input for itself.
+    pub fn exclusive_layer(&self) -> Option<LayerSurface> {
+        self.layer_shell_state
+            .layer_surfaces()
+            .rev()
+            .filter(|layer| {
+                compositor::with_states(layer.wl_surface(), |states| {
+                    let mut guard = states.cached_state.get::<LayerSurfaceCachedState>();
+                    let data = guard.current();
+
+                    data.keyboard_interactivity == KeyboardInteractivity::Exclusive
+                        && (data.layer == WlrLayer::Top || data.layer == WlrLayer::Overlay)
+                })
+            })
+            .find_map(|layer| {
+                self.space.outputs().find_map(|output| {
+                    let map = layer_map_for_output(output);
+                    map.layers().find(|l| l.layer_surface() == &layer).cloned()
+                })
+            })
+    }
+
     fn handle_pointer_motion<B: InputBackend>(&mut self, event: B::PointerMotionEvent) {
         let serial = SERIAL_COUNTER.next_serial();
         let delta = (event.delta_x(), event.delta_y()).into();
@@ -110,6 +119,7 @@ impl ProjectWC {
             },
         );
         pointer.frame(self);
+        self.update_sloppy_focus();
     }
 
     fn handle_pointer_motion_absolute<B: InputBackend>(
@@ -144,21 +154,21 @@ impl ProjectWC {
             },
         );
         pointer.frame(self);
+        self.update_sloppy_focus();
     }
 
     fn handle_pointer_button<B: InputBackend>(&mut self, event: B::PointerButtonEvent) {
         let serial = SERIAL_COUNTER.next_serial();
-        let button = event.button();
         let button_code = event.button_code();
         let button_state = event.state();
         let pointer = self.pointer();
 
         let keyboard = self.seat.get_keyboard().expect("keyboard not initialized");
-        let alt_held = keyboard.modifier_state().alt;
+        let modifiers = keyboard.modifier_state();
+        let mouse = self.config.mouse.clone();
 
         if ButtonState::Pressed == button_state
-            && button == Some(MouseButton::Left)
-            && alt_held
+            && mouse.move_window.matches(&modifiers, button_code)
             && let Some((window, _)) = self.window_under_pointer()
             && !pointer.is_grabbed()
         {
@@ -169,23 +179,20 @@ impl ProjectWC {
                 button: button_code,
                 location,
             };
-            let initial_window_location = self.space.element_location(&window).unwrap();
-            let grab = MoveGrab {
-                start_data,
-                window: window.clone(),
-                initial_window_location,
-            };
-            pointer.set_grab(self, grab, serial, Focus::Clear);
-            self.space.raise_element(&window, true);
+            if let Some(grab) = MoveGrab::start(start_data, window.clone(), self) {
+                pointer.set_grab(self, grab, serial, Focus::Clear);
+                self.focus_window(Some(&window));
+            }
+        } else if ButtonState::Pressed == button_state
+            && mouse.resize_window.matches(&modifiers, button_code)
+            && let Some((window, _)) = self.window_under_pointer()
+            && !pointer.is_grabbed()
+        {
+            self.start_pointer_resize(window, button_code, serial, event.time_msec());
         }
 
         if ButtonState::Pressed == button_state {
-            self.update_keyboard_focus(self.pointer_location, serial);
-            self.space.elements().for_each(|window| {
-                window
-                    .toplevel()
-                    .map(|toplevel| toplevel.send_pending_configure());
-            });
+            self.update_keyboard_focus(self.pointer_location);
         }
 
         pointer.button(
@@ -200,7 +207,50 @@ impl ProjectWC {
         pointer.frame(self);
     }
 
-    fn update_keyboard_focus(&mut self, location: Point<f64, Logical>, serial: Serial) {
+    /// Resizes `window` from the corner nearest to the pointer, warping the
+    /// pointer onto that corner first, like dwm's `resizemouse`.
+    fn start_pointer_resize(&mut self, window: Window, button: u32, serial: Serial, time: u32) {
+        // Leave the pointer where it is if nothing is going to be resized
+        if !self.can_resize(&window) {
+            return;
+        }
+        let Some(geometry) = self.space.element_geometry(&window) else {
+            return;
+        };
+
+        let center = geometry.loc.to_f64() + geometry.size.to_f64().downscale(2.0).to_point();
+        let (horizontal, x) = if self.pointer_location.x < center.x {
+            (ResizeEdge::LEFT, geometry.loc.x)
+        } else {
+            (ResizeEdge::RIGHT, geometry.loc.x + geometry.size.w - 1)
+        };
+        let (vertical, y) = if self.pointer_location.y < center.y {
+            (ResizeEdge::TOP, geometry.loc.y)
+        } else {
+            (ResizeEdge::BOTTOM, geometry.loc.y + geometry.size.h - 1)
+        };
+
+        self.pointer_location = Point::<i32, Logical>::from((x, y)).to_f64();
+        let pointer = self.pointer();
+        pointer.motion(
+            self,
+            None,
+            &MotionEvent {
+                location: self.pointer_location,
+                serial,
+                time,
+            },
+        );
+
+        let start_data = PointerGrabStartData {
+            focus: None,
+            button,
+            location: self.pointer_location,
+        };
+        self.start_resize(start_data, window, horizontal | vertical, serial);
+    }
+
+    fn update_keyboard_focus(&mut self, location: Point<f64, Logical>) {
         let keyboard = self.seat.get_keyboard().unwrap();
         let input_method = self.seat.input_method();
 
@@ -212,12 +262,18 @@ impl ProjectWC {
             let output = self.space.outputs().next().cloned().unwrap();
             let output_geo = self.space.output_geometry(&output).unwrap();
 
+            // Bars on the Top layer are hidden behind a focused fullscreen window
+            let top_hidden = self.is_fullscreen_focused();
             let layers = layer_map_for_output(&output);
 
             #[allow(clippy::collapsible_if)]
             if let Some(layer) = layers
                 .layer_under(WlrLayer::Overlay, location - output_geo.loc.to_f64())
-                .or_else(|| layers.layer_under(WlrLayer::Top, location - output_geo.loc.to_f64()))
+                .or_else(|| {
+                    layers
+                        .layer_under(WlrLayer::Top, location - output_geo.loc.to_f64())
+                        .filter(|_| !top_hidden)
+                })
             {
                 if layer.can_receive_keyboard_focus() {
                     tracing::debug!(
@@ -233,7 +289,7 @@ impl ProjectWC {
                     ) {
                         let namespace = layer.namespace();
                         tracing::debug!(namespace, "Set keyboard focus for layer");
-                        keyboard.set_focus(self, Some(layer.wl_surface().clone()), serial);
+                        self.focus_layer(layer.wl_surface());
                         return;
                     }
                 }
@@ -245,12 +301,7 @@ impl ProjectWC {
                 .map(|(w, p)| (w.clone(), p))
             {
                 tracing::debug!("Setting focus of surface under pointer");
-                self.space.raise_element(&window, true);
-                keyboard.set_focus(
-                    self,
-                    Some(window.toplevel().unwrap().wl_surface().clone()),
-                    serial,
-                );
+                self.focus_window(Some(&window));
                 return;
             }
 
@@ -268,7 +319,7 @@ impl ProjectWC {
                             - layers.layer_geometry(layer).unwrap().loc.to_f64(),
                         WindowSurfaceType::ALL,
                     ) {
-                        keyboard.set_focus(self, Some(layer.wl_surface().clone()), serial);
+                        self.focus_layer(layer.wl_surface());
                     }
                 }
             }
@@ -334,51 +385,3 @@ impl ProjectWC {
         }
     }
 }
-
-fn handle_keybinding(state: &mut ProjectWC, modifiers: &ModifiersState, keysym: Keysym) -> bool {
-    if !modifiers.alt {
-        return false;
-    }
-
-    match keysym {
-        Keysym::Escape => {
-            tracing::debug!("Quitting");
-            state.loop_signal.stop();
-            true
-        }
-        Keysym::Return => {
-            tracing::debug!("Spawning weston-terminal");
-            std::process::Command::new("weston-terminal").spawn().ok();
-            true
-        }
-        Keysym::q => {
-            let keyboard = state.seat.get_keyboard().unwrap();
-            if let Some(focused_surface) = keyboard.current_focus()
-                && let Some(window) = state.window_for_surface(&focused_surface)
-            {
-                tracing::info!("Closing focused window");
-                window.toplevel().unwrap().send_close();
-            }
-            true
-        }
-        Keysym::d => {
-            tracing::debug!("Spawning rofi menu");
-            let _ = std::process::Command::new("rofi")
-                .arg("-show")
-                .arg("drun")
-                .spawn();
-            true
-        }
-        Keysym::j => {
-            tracing::debug!("Focused next window");
-            Action::FocusNext.execute(state);
-            true
-        }
-        Keysym::k => {
-            tracing::debug!("Focused previous window");
-            Action::FocusPrevious.execute(state);
-            true
-        }
-        _ => false,
-    }
-}
@@ -0,0 +1,193 @@
+use std::{
+    ffi::OsStr,
+    io::{ErrorKind, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction, generic::Generic};
+
+use crate::{CompositorError, ProjectWC, Result};
+
+/// Environment variable pointing child processes at the IPC socket.
+pub const SOCKET_ENV: &str = "PROJECTWC_SOCKET";
+
+/// Commands accepted on the IPC socket, one per connection.
+pub enum IpcCommand {
+    Reload,
+    Bindings,
+    Layout,
+}
+
+impl FromStr for IpcCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reload" => Ok(Self::Reload),
+            "bindings" => Ok(Self::Bindings),
+            "layout" => Ok(Self::Layout),
+            _ => Err(format!("Invalid IPC command: {}", s.trim())),
+        }
+    }
+}
+
+impl IpcCommand {
+    pub fn execute(self, project_wc: &mut ProjectWC) -> String {
+        match self {
+            IpcCommand::Reload => match project_wc.reload_config() {
+                Ok(()) => "ok".into(),
+                Err(err) => format!("error: {err}"),
+            },
+            IpcCommand::Bindings => project_wc
+                .config
+                .keybindings
+                .iter()
+                .map(|(combo, action)| format!("{combo} {action:?}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            IpcCommand::Layout => project_wc
+                .active_workspace()
+                .layout
+                .symbol(project_wc.visible_windows().len()),
+        }
+    }
+}
+
+pub fn socket_path(socket_name: &OsStr) -> PathBuf {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+
+    runtime_dir.join(format!("projectwc-{}.sock", socket_name.to_string_lossy()))
+}
+
+pub fn init_ipc_listener(
+    loop_handle: &LoopHandle<'static, ProjectWC>,
+    socket_name: &OsStr,
+) -> Result<PathBuf> {
+    let path = socket_path(socket_name);
+
+    // A previous session may have left its socket behind
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path).map_err(|e| CompositorError::Ipc(e.to_string()))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| CompositorError::Ipc(e.to_string()))?;
+
+    loop_handle
+        .insert_source(
+            Generic::new(listener, Interest::READ, Mode::Level),
+            |_, listener, state| {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => handle_client(state, stream),
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                        Err(err) => {
+                            tracing::warn!("failed to accept IPC client: {err}");
+                            break;
+                        }
+                    }
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .map_err(|e| CompositorError::EventLoop(e.to_string()))?;
+
+    Ok(path)
+}
+
+/// Longest command line a client may send.
+const MAX_LINE: usize = 4096;
+
+/// Watches `stream` for its command line, so a slow client never holds up
+/// the event loop.
+fn handle_client(state: &mut ProjectWC, stream: UnixStream) {
+    if let Err(err) = stream.set_nonblocking(true) {
+        tracing::warn!("failed to make IPC client non-blocking: {err}");
+        return;
+    }
+
+    let mut line = Vec::new();
+    let source = Generic::new(stream, Interest::READ, Mode::Level);
+    let result = state
+        .loop_handle
+        .insert_source(source, move |_, stream, state| {
+            let mut buf = [0; 512];
+            let mut eof = false;
+            loop {
+                match (&**stream).read(&mut buf) {
+                    Ok(0) => {
+                        eof = true;
+                        break;
+                    }
+                    Ok(read) => line.extend_from_slice(&buf[..read]),
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(err) => {
+                        tracing::warn!("failed to read IPC command: {err}");
+                        return Ok(PostAction::Remove);
+                    }
+                }
+            }
+
+            let end = match line.iter().position(|&byte| byte == b'\n') {
+                Some(end) => end,
+                None if line.len() > MAX_LINE => {
+                    reply(stream, "error: command too long");
+                    return Ok(PostAction::Remove);
+                }
+                // The client hung up without sending anything
+                None if eof && line.is_empty() => return Ok(PostAction::Remove),
+                // Or it shut down its side instead of ending the line
+                None if eof => line.len(),
+                None => return Ok(PostAction::Continue),
+            };
+
+            let response = match std::str::from_utf8(&line[..end]) {
+                Ok(command) => match command.parse::<IpcCommand>() {
+                    Ok(command) => command.execute(state),
+                    Err(err) => format!("error: {err}"),
+                },
+                Err(err) => format!("error: {err}"),
+            };
+            reply(stream, &response);
+            Ok(PostAction::Remove)
+        });
+
+    if let Err(err) = result {
+        tracing::warn!("failed to watch IPC client: {err}");
+    }
+}
+
+/// Writes `response` back to the client. Replies are short, so a brief
+/// blocking write is fine here.
+fn reply(stream: &UnixStream, response: &str) {
+    let result = stream
+        .set_nonblocking(false)
+        .and_then(|()| stream.set_write_timeout(Some(Duration::from_millis(100))))
+        .and_then(|()| writeln!(&*stream, "{response}"));
+    if let Err(err) = result {
+        tracing::warn!("failed to write IPC reply: {err}");
+    }
+}
+
+/// Sends `command` to the running compositor and returns its reply.
+pub fn send_command(command: &str) -> Result<String> {
+    let path = std::env::var_os(SOCKET_ENV)
+        .ok_or_else(|| CompositorError::Ipc(format!("{SOCKET_ENV} is not set")))?;
+
+    let mut stream = UnixStream::connect(path).map_err(|e| CompositorError::Ipc(e.to_string()))?;
+    writeln!(stream, "{command}").map_err(|e| CompositorError::Ipc(e.to_string()))?;
+
+    // The compositor closes the connection once the reply is written
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .map_err(|e| CompositorError::Ipc(e.to_string()))?;
+
+    Ok(reply.trim_end().to_string())
+}
@@ -0,0 +1,393 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, de::Error as _};
+use smithay::input::keyboard::{Keysym, KeysymHandle, ModifiersState, xkb};
+
+use crate::{
+    action::{Action, Direction},
+    layout::LayoutType,
+    workspace::{TAG_COUNT, TagMask},
+};
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Modifiers: u32 {
+        const SHIFT = 0b0001;
+        const CTRL  = 0b0010;
+        const ALT   = 0b0100;
+        const SUPER = 0b1000;
+    }
+}
+
+impl From<&ModifiersState> for Modifiers {
+    fn from(state: &ModifiersState) -> Self {
+        let mut modifiers = Self::empty();
+        modifiers.set(Self::SHIFT, state.shift);
+        modifiers.set(Self::CTRL, state.ctrl);
+        modifiers.set(Self::ALT, state.alt);
+        modifiers.set(Self::SUPER, state.logo);
+        modifiers
+    }
+}
+
+/// A modifier mask plus a keysym, written as e.g. `Super+Shift+Return`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyCombo {
+    pub modifiers: Modifiers,
+    pub keysym: Keysym,
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            return Err(format!("Missing key in binding: {s}"));
+        };
+
+        let modifiers = parse_modifiers(&parts)?;
+
+        let mut keysym = xkb::keysym_from_name(key, xkb::KEYSYM_NO_FLAGS);
+        if keysym == Keysym::NoSymbol {
+            keysym = xkb::keysym_from_name(key, xkb::KEYSYM_CASE_INSENSITIVE);
+        }
+        if keysym == Keysym::NoSymbol {
+            return Err(format!("Invalid key: {key}"));
+        }
+
+        Ok(Self {
+            modifiers,
+            keysym: lowercase(keysym),
+        })
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_modifiers(f, self.modifiers)?;
+        write!(f, "{}", xkb::keysym_get_name(self.keysym))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let combo = String::deserialize(deserializer)?;
+        combo.parse().map_err(D::Error::custom)
+    }
+}
+
+// Button codes from linux/input-event-codes.h
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+pub const BTN_MIDDLE: u32 = 0x112;
+
+/// A modifier mask plus a pointer button, written as e.g. `Alt+Left`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonCombo {
+    pub modifiers: Modifiers,
+    pub button: u32,
+}
+
+impl ButtonCombo {
+    pub fn matches(&self, modifiers: &ModifiersState, button: u32) -> bool {
+        self.modifiers == Modifiers::from(modifiers) && self.button == button
+    }
+}
+
+impl FromStr for ButtonCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let button = parts.pop().filter(|button| !button.is_empty());
+        let Some(button) = button else {
+            return Err(format!("Missing button in binding: {s}"));
+        };
+
+        let modifiers = parse_modifiers(&parts)?;
+        let button = match button.to_lowercase().as_str() {
+            "left" => BTN_LEFT,
+            "right" => BTN_RIGHT,
+            "middle" => BTN_MIDDLE,
+            _ => return Err(format!("Invalid button: {button}")),
+        };
+
+        Ok(Self { modifiers, button })
+    }
+}
+
+impl fmt::Display for ButtonCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_modifiers(f, self.modifiers)?;
+        match self.button {
+            BTN_LEFT => write!(f, "Left"),
+            BTN_RIGHT => write!(f, "Right"),
+            BTN_MIDDLE => write!(f, "Middle"),
+            button => write!(f, "{button:#x}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ButtonCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let combo = String::deserialize(deserializer)?;
+        combo.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Pointer bindings that grab the window under the pointer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MouseBindings {
+    #[serde(rename = "move")]
+    pub move_window: ButtonCombo,
+    /// Resizes from the corner nearest to the pointer.
+    #[serde(rename = "resize")]
+    pub resize_window: ButtonCombo,
+}
+
+impl Default for MouseBindings {
+    fn default() -> Self {
+        Self {
+            move_window: "Alt+Left".parse().expect("invalid default binding"),
+            resize_window: "Alt+Right".parse().expect("invalid default binding"),
+        }
+    }
+}
+
+/// The keybinding table, replaced as a whole by `[keybindings]` in the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Keybindings(BTreeMap<KeyCombo, Action>);
+
+impl Keybindings {
+    /// Looks up the action bound to a key press.
+    ///
+    /// The keysym with modifiers applied is tried first, then the raw ones, so
+    /// `Alt+Shift+1` matches even though Shift turns the 1 into an exclam.
+    pub fn action_for(
+        &self,
+        modifiers: &ModifiersState,
+        keysym: &KeysymHandle<'_>,
+    ) -> Option<Action> {
+        let keysyms =
+            std::iter::once(keysym.modified_sym()).chain(keysym.raw_syms().iter().copied());
+        self.lookup(Modifiers::from(modifiers), keysyms)
+    }
+
+    /// The action bound to the first of `keysyms` that has one.
+    fn lookup(
+        &self,
+        modifiers: Modifiers,
+        keysyms: impl IntoIterator<Item = Keysym>,
+    ) -> Option<Action> {
+        keysyms
+            .into_iter()
+            .find_map(|keysym| {
+                self.0.get(&KeyCombo {
+                    modifiers,
+                    keysym: lowercase(keysym),
+                })
+            })
+            .cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&KeyCombo, &Action)> {
+        self.0.iter()
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let spawn = |argv: &[&str]| Action::Spawn(argv.iter().map(|arg| arg.to_string()).collect());
+
+        let bindings = [
+            ("Alt+Escape", Action::Quit),
+            ("Alt+Return", spawn(&["weston-terminal"])),
+            ("Alt+q", Action::CloseWindow),
+            ("Alt+d", spawn(&["rofi", "-show", "drun"])),
+            ("Alt+j", Action::FocusNext),
+            ("Alt+k", Action::FocusPrevious),
+            ("Alt+Left", Action::FocusDirection(Direction::Left)),
+            ("Alt+Right", Action::FocusDirection(Direction::Right)),
+            ("Alt+Up", Action::FocusDirection(Direction::Up)),
+            ("Alt+Down", Action::FocusDirection(Direction::Down)),
+            ("Alt+Shift+Left", Action::MoveDirection(Direction::Left)),
+            ("Alt+Shift+Right", Action::MoveDirection(Direction::Right)),
+            ("Alt+Shift+Up", Action::MoveDirection(Direction::Up)),
+            ("Alt+Shift+Down", Action::MoveDirection(Direction::Down)),
+            ("Alt+Shift+Return", Action::Zoom),
+            ("Alt+Shift+j", Action::MoveNext),
+            ("Alt+Shift+k", Action::MovePrevious),
+            ("Alt+Shift+r", Action::ReloadConfig),
+            ("Alt+Tab", Action::SwitchWindows),
+            ("Alt+Shift+Tab", Action::SwitchWindowsBackward),
+            ("Alt+grave", Action::ViewPrevious),
+            ("Alt+0", Action::ViewAll),
+            ("Alt+t", Action::SetLayout(LayoutType::Tiling)),
+            ("Alt+m", Action::SetLayout(LayoutType::Monocle)),
+            ("Alt+space", Action::CycleLayout),
+            ("Alt+h", Action::IncMasterFactor(-0.05)),
+            ("Alt+l", Action::IncMasterFactor(0.05)),
+            ("Alt+i", Action::IncNumMaster(1)),
+            ("Alt+o", Action::IncNumMaster(-1)),
+            ("Alt+Shift+h", Action::ResetMasterFactor),
+            ("Alt+Shift+i", Action::ResetNumMaster),
+            ("Alt+Ctrl+h", Action::IncWindowFactor(-0.25)),
+            ("Alt+Ctrl+l", Action::IncWindowFactor(0.25)),
+            ("Alt+Ctrl+o", Action::ResetWindowFactor),
+            ("Alt+Shift+space", Action::ToggleFloating),
+            ("Alt+f", Action::ToggleFullscreen),
+        ];
+
+        let tags = (1..=TAG_COUNT).flat_map(|num| {
+            let mask = TagMask::tag(num);
+            [
+                (format!("Alt+{num}"), Action::View(mask)),
+                (format!("Alt+Ctrl+{num}"), Action::ToggleView(mask)),
+                (format!("Alt+Shift+{num}"), Action::Tag(mask)),
+                (format!("Alt+Ctrl+Shift+{num}"), Action::ToggleTag(mask)),
+            ]
+        });
+
+        Self(
+            bindings
+                .into_iter()
+                .map(|(combo, action)| (combo.to_string(), action))
+                .chain(tags)
+                .map(|(combo, action)| (combo.parse().expect("invalid default binding"), action))
+                .collect(),
+        )
+    }
+}
+
+fn parse_modifiers(parts: &[&str]) -> Result<Modifiers, String> {
+    let mut modifiers = Modifiers::empty();
+    for part in parts {
+        modifiers |= match part.to_lowercase().as_str() {
+            "shift" => Modifiers::SHIFT,
+            "ctrl" | "control" => Modifiers::CTRL,
+            "alt" | "mod1" => Modifiers::ALT,
+            "super" | "logo" | "mod4" => Modifiers::SUPER,
+            _ => return Err(format!("Invalid modifier: {part}")),
+        };
+    }
+    Ok(modifiers)
+}
+
+fn write_modifiers(f: &mut fmt::Formatter<'_>, modifiers: Modifiers) -> fmt::Result {
+    for (name, modifier) in [
+        ("Super", Modifiers::SUPER),
+        ("Ctrl", Modifiers::CTRL),
+        ("Alt", Modifiers::ALT),
+        ("Shift", Modifiers::SHIFT),
+    ] {
+        if modifiers.contains(modifier) {
+            write!(f, "{name}+")?;
+        }
+    }
+    Ok(())
+}
+
+/// Bindings are stored with lowercase letters so `Alt+Q` and `Alt+q` match alike.
+fn lowercase(keysym: Keysym) -> Keysym {
+    match keysym.key_char() {
+        Some(ch) if ch.is_uppercase() => ch
+            .to_lowercase()
+            .next()
+            .map(Keysym::from_char)
+            .unwrap_or(keysym),
+        _ => keysym,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo(s: &str) -> KeyCombo {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn key_combo_folds_case() {
+        assert_eq!(combo("Alt+Q"), combo("Alt+q"));
+        assert_eq!(combo("Alt+q").keysym, Keysym::q);
+        // Keysym names are matched case-insensitively as a fallback
+        assert_eq!(combo("alt+return").keysym, Keysym::Return);
+    }
+
+    #[test]
+    fn key_combo_accepts_modifier_aliases() {
+        assert_eq!(combo("Control+Mod1+Logo+x"), combo("Ctrl+Alt+Super+x"));
+        assert_eq!(combo("mod4+SHIFT+x"), combo("Super+Shift+x"));
+        assert_eq!(
+            combo(" Super + Shift + Return ").modifiers,
+            Modifiers::SUPER | Modifiers::SHIFT
+        );
+    }
+
+    #[test]
+    fn key_combo_rejects_bad_input() {
+        assert_eq!(
+            "Alt+NotAKey".parse::<KeyCombo>(),
+            Err("Invalid key: NotAKey".into())
+        );
+        assert_eq!(
+            "Hyper+x".parse::<KeyCombo>(),
+            Err("Invalid modifier: Hyper".into())
+        );
+        assert_eq!(
+            "Alt+".parse::<KeyCombo>(),
+            Err("Missing key in binding: Alt+".into())
+        );
+    }
+
+    #[test]
+    fn key_combo_display_round_trips() {
+        let parsed = combo("shift+super+Return");
+        assert_eq!(parsed.to_string(), "Super+Shift+Return");
+        assert_eq!(combo(&parsed.to_string()), parsed);
+    }
+
+    #[test]
+    fn button_combo_parses() {
+        let parsed: ButtonCombo = "super+RIGHT".parse().unwrap();
+        assert_eq!(parsed.modifiers, Modifiers::SUPER);
+        assert_eq!(parsed.button, BTN_RIGHT);
+        assert_eq!(parsed.to_string(), "Super+Right");
+
+        assert_eq!(
+            "Alt+Back".parse::<ButtonCombo>(),
+            Err("Invalid button: Back".into())
+        );
+        assert_eq!(
+            "Alt".parse::<ButtonCombo>(),
+            Err("Invalid button: Alt".into())
+        );
+    }
+
+    #[test]
+    fn shifted_digits_fall_back_to_raw_keysyms() {
+        let bindings = Keybindings::default();
+        let modifiers = Modifiers::ALT | Modifiers::SHIFT;
+
+        // Shift turns the 1 into an exclam, which has no binding of its own
+        assert_eq!(bindings.lookup(modifiers, [Keysym::exclam]), None);
+        assert_eq!(
+            bindings.lookup(modifiers, [Keysym::exclam, Keysym::_1]),
+            Some(Action::Tag(TagMask::tag(1)))
+        );
+    }
+
+    #[test]
+    fn shifted_letters_match_lowercase_bindings() {
+        let bindings = Keybindings::default();
+        assert_eq!(
+            bindings.lookup(Modifiers::ALT | Modifiers::SHIFT, [Keysym::J]),
+            Some(Action::MoveNext)
+        );
+    }
+}
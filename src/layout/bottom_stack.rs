@@ -0,0 +1,103 @@
+use super::{GapConfig, Layout, WindowGeometry, tiling::Tiling};
+pub struct BottomStack;
+
+impl Layout for BottomStack {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = client_factors.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let gap_values = Tiling::getgaps(gaps, window_count, smartgaps_enabled);
+
+        let outer_gap_horizontal = gap_values.outer_horizontal;
+        let outer_gap_vertical = gap_values.outer_vertical;
+        let inner_gap_horizontal = gap_values.inner_horizontal;
+        let inner_gap_vertical = gap_values.inner_vertical;
+
+        let mut master_x = outer_gap_vertical as i32;
+        let master_y = outer_gap_horizontal as i32;
+        let mut stack_x = master_x;
+        let mut stack_y = master_y;
+
+        let num_master_usize = num_master.max(0) as usize;
+        let master_count = window_count.min(num_master_usize);
+        let stack_count = window_count.saturating_sub(num_master_usize);
+
+        // Masters sit side by side in the top row, the stack fills the row below
+        let master_width = (screen_width as i32)
+            - (2 * outer_gap_vertical) as i32
+            - (inner_gap_vertical as i32 * master_count.saturating_sub(1) as i32);
+        let stack_width = (screen_width as i32)
+            - (2 * outer_gap_vertical) as i32
+            - (inner_gap_vertical as i32 * stack_count.saturating_sub(1) as i32);
+        let mut master_height = (screen_height as i32) - (2 * outer_gap_horizontal) as i32;
+        let mut stack_height = master_height;
+
+        if num_master > 0 && window_count > num_master_usize {
+            stack_height = ((master_height as f32 - inner_gap_horizontal as f32)
+                * (1.0 - master_factor)) as i32;
+            master_height = master_height - inner_gap_horizontal as i32 - stack_height;
+            stack_y = master_y + master_height + inner_gap_horizontal as i32;
+        }
+
+        let facts = Tiling::getfacts(client_factors, num_master, master_width, stack_width);
+
+        let mut geometries = Vec::new();
+
+        for (i, &cfact) in client_factors.iter().enumerate() {
+            if i < num_master_usize {
+                let window_width = Tiling::share(master_width, cfact, facts.master_facts)
+                    + if (i as i32) < facts.master_remainder {
+                        1
+                    } else {
+                        0
+                    };
+
+                geometries.push(WindowGeometry {
+                    x_coordinate: master_x,
+                    y_coordinate: master_y,
+                    width: window_width as u32,
+                    height: master_height as u32,
+                });
+
+                master_x += window_width + inner_gap_vertical as i32;
+            } else {
+                let window_width = if facts.stack_facts > 0.0 {
+                    Tiling::share(stack_width, cfact, facts.stack_facts)
+                        + if ((i - num_master_usize) as i32) < facts.stack_remainder {
+                            1
+                        } else {
+                            0
+                        }
+                } else {
+                    stack_width
+                };
+
+                geometries.push(WindowGeometry {
+                    x_coordinate: stack_x,
+                    y_coordinate: stack_y,
+                    width: window_width as u32,
+                    height: stack_height as u32,
+                });
+
+                stack_x += window_width + inner_gap_vertical as i32;
+            }
+        }
+
+        geometries
+    }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "TTT".into()
+    }
+}
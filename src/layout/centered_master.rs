@@ -0,0 +1,138 @@
+use super::{GapConfig, Layout, WindowGeometry, tiling::Tiling};
+pub struct CenteredMaster;
+
+/// A column of windows stacked vertically.
+struct Column {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    /// Size weights of the windows in the column, top to bottom.
+    factors: Vec<f32>,
+}
+
+impl Column {
+    /// Height of the `idx`th window by its weight, spreading the rounding
+    /// remainder over the first ones.
+    fn window_height(&self, idx: usize) -> i32 {
+        let count = self.factors.len() as i32;
+        let facts = Tiling::getfacts(&self.factors, count, self.height, 0);
+        Tiling::share(self.height, self.factors[idx], facts.master_facts)
+            + if (idx as i32) < facts.master_remainder {
+                1
+            } else {
+                0
+            }
+    }
+}
+
+impl Layout for CenteredMaster {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = client_factors.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let gap_values = Tiling::getgaps(gaps, window_count, smartgaps_enabled);
+
+        let outer_gap_horizontal = gap_values.outer_horizontal as i32;
+        let outer_gap_vertical = gap_values.outer_vertical as i32;
+        let inner_gap_horizontal = gap_values.inner_horizontal as i32;
+        let inner_gap_vertical = gap_values.inner_vertical as i32;
+
+        let screen_width = screen_width as i32;
+        let screen_height = screen_height as i32;
+        let window_count = window_count as i32;
+        let num_master = num_master.max(0);
+
+        // Without masters every window goes into the center column
+        let master_count = if num_master == 0 {
+            window_count
+        } else {
+            window_count.min(num_master)
+        };
+        let stack_count = window_count - master_count;
+        let (master_factors, stack_factors) = client_factors.split_at(master_count as usize);
+        // Stack windows alternate right, left, right, ...
+        let right_factors: Vec<f32> = stack_factors.iter().step_by(2).copied().collect();
+        let left_factors: Vec<f32> = stack_factors.iter().skip(1).step_by(2).copied().collect();
+
+        let column_height = |count: i32| {
+            screen_height - 2 * outer_gap_horizontal - inner_gap_horizontal * (count - 1).max(0)
+        };
+
+        let mut master = Column {
+            x: outer_gap_vertical,
+            y: outer_gap_horizontal,
+            width: screen_width - 2 * outer_gap_vertical,
+            height: column_height(master_count),
+            factors: master_factors.to_vec(),
+        };
+        let mut left = Column {
+            x: outer_gap_vertical,
+            y: outer_gap_horizontal,
+            width: 0,
+            height: column_height(left_factors.len() as i32),
+            factors: left_factors,
+        };
+        let mut right = Column {
+            x: outer_gap_vertical,
+            y: outer_gap_horizontal,
+            width: 0,
+            height: column_height(right_factors.len() as i32),
+            factors: right_factors,
+        };
+
+        if stack_count > 1 {
+            // ||<-S->|<---M--->|<-S->||
+            let usable = screen_width - 2 * outer_gap_vertical - 2 * inner_gap_vertical;
+            master.width = (usable as f32 * master_factor) as i32;
+            left.width = (usable - master.width) / 2;
+            right.width = usable - master.width - left.width;
+            master.x += left.width + inner_gap_vertical;
+        } else if stack_count == 1 {
+            // ||<---M--->|<-S->||
+            master.width = ((master.width - inner_gap_vertical) as f32 * master_factor) as i32;
+            right.width = screen_width - master.width - inner_gap_vertical - 2 * outer_gap_vertical;
+        }
+        right.x = master.x + master.width + inner_gap_vertical;
+
+        let mut geometries = Vec::new();
+
+        for i in 0..window_count {
+            let (column, idx) = if i < master_count {
+                (&mut master, i)
+            } else if (i - master_count) % 2 == 1 {
+                (&mut left, (i - master_count) / 2)
+            } else {
+                (&mut right, (i - master_count) / 2)
+            };
+
+            let window_height = column.window_height(idx as usize);
+
+            geometries.push(WindowGeometry {
+                x_coordinate: column.x,
+                y_coordinate: column.y,
+                width: column.width as u32,
+                height: window_height as u32,
+            });
+
+            column.y += window_height + inner_gap_horizontal;
+        }
+
+        geometries
+    }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "|M|".into()
+    }
+}
@@ -0,0 +1,161 @@
+use super::{GapConfig, Layout, WindowGeometry, tiling::Tiling};
+
+/// Each window takes half of the remaining area, turning inwards clockwise.
+pub struct Spiral;
+
+/// Each window takes half of the remaining area, shrinking towards the
+/// bottom right.
+pub struct Dwindle;
+
+/// Shared by `Spiral` and `Dwindle`. Gaps are dropped once an area gets too
+/// thin for them, but every split still halves what is left, so after about
+/// as many windows as there are halvings in the output's size (15 on
+/// 800x600) the remaining windows share the last, single pixel cell.
+fn fibonacci(
+    window_count: usize,
+    screen_width: u32,
+    screen_height: u32,
+    gaps: &GapConfig,
+    master_factor: f32,
+    smartgaps_enabled: bool,
+    spiral: bool,
+) -> Vec<WindowGeometry> {
+    if window_count == 0 {
+        return Vec::new();
+    }
+
+    let gap_values = Tiling::getgaps(gaps, window_count, smartgaps_enabled);
+
+    let outer_gap_horizontal = gap_values.outer_horizontal as i32;
+    let outer_gap_vertical = gap_values.outer_vertical as i32;
+    let inner_gap_horizontal = gap_values.inner_horizontal as i32;
+    let inner_gap_vertical = gap_values.inner_vertical as i32;
+
+    let mut x = outer_gap_vertical;
+    let mut y = outer_gap_horizontal;
+    let mut width = screen_width as i32 - 2 * outer_gap_vertical;
+    let mut height = screen_height as i32 - 2 * outer_gap_horizontal;
+
+    let geometry = |x: i32, y: i32, width: i32, height: i32| WindowGeometry {
+        x_coordinate: x,
+        y_coordinate: y,
+        width: width.max(0) as u32,
+        height: height.max(0) as u32,
+    };
+
+    let mut geometries = Vec::with_capacity(window_count);
+
+    for i in 0..window_count {
+        if i == window_count - 1 {
+            geometries.push(geometry(x, y, width, height));
+            break;
+        }
+
+        // Even steps split side by side, odd steps split top and bottom,
+        // unless the area is too thin to split that way
+        let side_by_side = match (width >= 2, height >= 2) {
+            (true, true) => i % 2 == 0,
+            (true, false) => true,
+            (false, true) => false,
+            // Not even a pixel per window is left, so the rest share it
+            (false, false) => {
+                geometries.extend((i..window_count).map(|_| geometry(x, y, width, height)));
+                break;
+            }
+        };
+        let size = if side_by_side { width } else { height };
+        // Gaps go before windows do
+        let gap = match side_by_side {
+            true if width - inner_gap_vertical >= 2 => inner_gap_vertical,
+            false if height - inner_gap_horizontal >= 2 => inner_gap_horizontal,
+            _ => 0,
+        };
+
+        // The master split honours the master factor, later ones halve
+        let window_size = if i == 0 {
+            ((size - gap) as f32 * master_factor) as i32
+        } else {
+            (size - gap) / 2
+        }
+        .clamp(1, size - gap - 1);
+        let rest_size = size - gap - window_size;
+
+        // A spiral puts every third window on the right and every fourth at
+        // the bottom, so the remaining area turns inwards.
+        let window_last = spiral && i % 4 >= 2;
+
+        if side_by_side {
+            if window_last {
+                geometries.push(geometry(x + rest_size + gap, y, window_size, height));
+            } else {
+                geometries.push(geometry(x, y, window_size, height));
+                x += window_size + gap;
+            }
+            width = rest_size;
+        } else {
+            if window_last {
+                geometries.push(geometry(x, y + rest_size + gap, width, window_size));
+            } else {
+                geometries.push(geometry(x, y, width, window_size));
+                y += window_size + gap;
+            }
+            height = rest_size;
+        }
+    }
+
+    geometries
+}
+
+impl Layout for Spiral {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        fibonacci(
+            client_factors.len(),
+            screen_width,
+            screen_height,
+            gaps,
+            master_factor,
+            smartgaps_enabled,
+            true,
+        )
+    }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "[@]".into()
+    }
+}
+
+impl Layout for Dwindle {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        fibonacci(
+            client_factors.len(),
+            screen_width,
+            screen_height,
+            gaps,
+            master_factor,
+            smartgaps_enabled,
+            false,
+        )
+    }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "[\\]".into()
+    }
+}
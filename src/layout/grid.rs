@@ -0,0 +1,81 @@
+use super::{GapConfig, Layout, WindowGeometry, tiling::Tiling};
+pub struct Grid;
+
+impl Grid {
+    /// Smallest near-square grid holding `window_count` cells, as `(rows,
+    /// cols)`. Outputs are wider than high, so a spare line goes to columns.
+    fn dimensions(window_count: usize) -> (usize, usize) {
+        let mut cols = 1;
+        while cols * cols < window_count {
+            cols += 1;
+        }
+        let rows = if (cols - 1) * cols >= window_count {
+            cols - 1
+        } else {
+            cols
+        };
+
+        (rows.max(1), cols)
+    }
+}
+
+impl Layout for Grid {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        _master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = client_factors.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let gap_values = Tiling::getgaps(gaps, window_count, smartgaps_enabled);
+
+        let outer_gap_horizontal = gap_values.outer_horizontal as i32;
+        let outer_gap_vertical = gap_values.outer_vertical as i32;
+        let inner_gap_horizontal = gap_values.inner_horizontal as i32;
+        let inner_gap_vertical = gap_values.inner_vertical as i32;
+
+        let (rows, cols) = Self::dimensions(window_count);
+        let (rows, cols) = (rows as i32, cols as i32);
+
+        let usable_height =
+            screen_height as i32 - 2 * outer_gap_horizontal - inner_gap_horizontal * (rows - 1);
+        let usable_width =
+            screen_width as i32 - 2 * outer_gap_vertical - inner_gap_vertical * (cols - 1);
+
+        let cell_height = usable_height / rows;
+        let cell_width = usable_width / cols;
+        let height_remainder = usable_height - cell_height * rows;
+        let width_remainder = usable_width - cell_width * cols;
+
+        // Cells are filled row by row
+        (0..window_count as i32)
+            .map(|i| {
+                let row = i / cols;
+                let col = i % cols;
+
+                WindowGeometry {
+                    x_coordinate: outer_gap_vertical
+                        + col * (cell_width + inner_gap_vertical)
+                        + col.min(width_remainder),
+                    y_coordinate: outer_gap_horizontal
+                        + row * (cell_height + inner_gap_horizontal)
+                        + row.min(height_remainder),
+                    width: (cell_width + if col < width_remainder { 1 } else { 0 }) as u32,
+                    height: (cell_height + if row < height_remainder { 1 } else { 0 }) as u32,
+                }
+            })
+            .collect()
+    }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "###".into()
+    }
+}
@@ -1,9 +1,16 @@
+pub mod bottom_stack;
+pub mod centered_master;
+pub mod fibonacci;
+pub mod grid;
+pub mod monocle;
 pub mod tiling;
 
-use smithay::desktop::Window;
+use serde::{Deserialize, Deserializer, de::Error as _};
 
 pub type LayoutBox = Box<dyn Layout>;
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct GapConfig {
     pub inner_horizontal: u32,
     pub inner_vertical: u32,
@@ -11,28 +18,84 @@ pub struct GapConfig {
     pub outer_vertical: u32,
 }
 
+impl Default for GapConfig {
+    fn default() -> Self {
+        Self {
+            inner_horizontal: 10,
+            inner_vertical: 10,
+            outer_horizontal: 20,
+            outer_vertical: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LayoutType {
     Tiling,
+    Monocle,
+    BottomStack,
+    CenteredMaster,
+    Grid,
+    Spiral,
+    Dwindle,
 }
 
 impl LayoutType {
+    /// Every layout, in the order [`LayoutType::next`] cycles through them.
+    pub const ALL: [LayoutType; 7] = [
+        LayoutType::Tiling,
+        LayoutType::Monocle,
+        LayoutType::BottomStack,
+        LayoutType::CenteredMaster,
+        LayoutType::Grid,
+        LayoutType::Spiral,
+        LayoutType::Dwindle,
+    ];
+
     pub fn new(&self) -> LayoutBox {
         match self {
             LayoutType::Tiling => Box::new(tiling::Tiling),
+            LayoutType::Monocle => Box::new(monocle::Monocle),
+            LayoutType::BottomStack => Box::new(bottom_stack::BottomStack),
+            LayoutType::CenteredMaster => Box::new(centered_master::CenteredMaster),
+            LayoutType::Grid => Box::new(grid::Grid),
+            LayoutType::Spiral => Box::new(fibonacci::Spiral),
+            LayoutType::Dwindle => Box::new(fibonacci::Dwindle),
         }
     }
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s.to_lowercase().as_str() {
             "tiling" => Ok(Self::Tiling),
+            "monocle" => Ok(Self::Monocle),
+            "bottomstack" | "bstack" => Ok(Self::BottomStack),
+            "centeredmaster" => Ok(Self::CenteredMaster),
+            "grid" => Ok(Self::Grid),
+            "spiral" => Ok(Self::Spiral),
+            "dwindle" => Ok(Self::Dwindle),
             _ => Err(format!("Invalid Layout Type: {}", s)),
         }
     }
+
+    /// The layout after this one, wrapping around at the end.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&layout| layout == self).unwrap();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+impl<'de> Deserialize<'de> for LayoutType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        LayoutType::from_str(&name).map_err(D::Error::custom)
+    }
 }
 
 pub trait Layout {
+    /// Places one window per entry in `client_factors`, each entry being that
+    /// window's size weight relative to its neighbours, like dwm's cfacts.
     fn arrange(
         &self,
-        windows: &[Window],
+        client_factors: &[f32],
         screen_width: u32,
         screen_height: u32,
         gaps: &GapConfig,
@@ -40,6 +103,9 @@ pub trait Layout {
         num_master: i32,
         smartgaps_enabled: bool,
     ) -> Vec<WindowGeometry>;
+
+    /// Short indicator for status bars, like dwm's `[]=`.
+    fn symbol(&self, window_count: usize) -> String;
 }
 
 #[derive(Clone)]
@@ -49,3 +115,168 @@ pub struct WindowGeometry {
     pub width: u32,
     pub height: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREENS: [(u32, u32); 3] = [(1920, 1080), (1080, 1920), (800, 600)];
+
+    fn gap_configs() -> [GapConfig; 2] {
+        [
+            GapConfig::default(),
+            GapConfig {
+                inner_horizontal: 0,
+                inner_vertical: 0,
+                outer_horizontal: 0,
+                outer_vertical: 0,
+            },
+        ]
+    }
+
+    fn overlaps(a: &WindowGeometry, b: &WindowGeometry) -> bool {
+        a.x_coordinate < b.x_coordinate + b.width as i32
+            && b.x_coordinate < a.x_coordinate + a.width as i32
+            && a.y_coordinate < b.y_coordinate + b.height as i32
+            && b.y_coordinate < a.y_coordinate + a.height as i32
+    }
+
+    fn assert_tiles(layout_type: LayoutType, max_windows: usize) {
+        assert_tiles_with_masters(layout_type, max_windows, 1);
+    }
+
+    fn assert_tiles_with_masters(layout_type: LayoutType, max_windows: usize, num_master: i32) {
+        let layout = layout_type.new();
+
+        for (screen_width, screen_height) in SCREENS {
+            for gaps in gap_configs() {
+                for smartgaps in [true, false] {
+                    for window_count in 1..=max_windows {
+                        // Uneven weights, as set with inc-window-factor
+                        let client_factors: Vec<f32> = [1.0, 2.0, 0.5, 1.5]
+                            .into_iter()
+                            .cycle()
+                            .take(window_count)
+                            .collect();
+                        let geometries = layout.arrange(
+                            &client_factors,
+                            screen_width,
+                            screen_height,
+                            &gaps,
+                            0.55,
+                            num_master,
+                            smartgaps,
+                        );
+                        let case = format!(
+                            "{layout_type:?} with {window_count} windows and {num_master} masters on {screen_width}x{screen_height}"
+                        );
+
+                        assert_eq!(geometries.len(), window_count, "{case}");
+
+                        for geom in &geometries {
+                            assert!(geom.width > 0 && geom.height > 0, "{case}");
+                            assert!(geom.x_coordinate >= 0, "{case}");
+                            assert!(geom.y_coordinate >= 0, "{case}");
+                            assert!(
+                                geom.x_coordinate + geom.width as i32 <= screen_width as i32,
+                                "{case}"
+                            );
+                            assert!(
+                                geom.y_coordinate + geom.height as i32 <= screen_height as i32,
+                                "{case}"
+                            );
+                        }
+
+                        for (i, a) in geometries.iter().enumerate() {
+                            for b in &geometries[i + 1..] {
+                                assert!(!overlaps(a, b), "{case}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tiling_does_not_overlap() {
+        assert_tiles(LayoutType::Tiling, 8);
+    }
+
+    #[test]
+    fn bottom_stack_does_not_overlap() {
+        assert_tiles(LayoutType::BottomStack, 8);
+    }
+
+    #[test]
+    fn centered_master_does_not_overlap() {
+        // No stack, a single stack column, and stacks on both sides
+        for num_master in [0, 1, 2] {
+            assert_tiles_with_masters(LayoutType::CenteredMaster, 8, num_master);
+        }
+    }
+
+    #[test]
+    fn grid_does_not_overlap() {
+        assert_tiles(LayoutType::Grid, 16);
+    }
+
+    #[test]
+    fn spiral_does_not_overlap() {
+        assert_tiles(LayoutType::Spiral, 15);
+    }
+
+    #[test]
+    fn dwindle_does_not_overlap() {
+        assert_tiles(LayoutType::Dwindle, 15);
+    }
+
+    #[test]
+    fn fibonacci_shares_only_once_out_of_pixels() {
+        for layout_type in [LayoutType::Spiral, LayoutType::Dwindle] {
+            let geometries = layout_type.new().arrange(
+                &[1.0; 40],
+                800,
+                600,
+                &GapConfig::default(),
+                0.55,
+                1,
+                false,
+            );
+            assert_eq!(geometries.len(), 40);
+
+            // Windows only overlap by sharing the same last cell
+            for (i, a) in geometries.iter().enumerate() {
+                assert!(a.width > 0 && a.height > 0);
+                for b in &geometries[i + 1..] {
+                    let same = (a.x_coordinate, a.y_coordinate, a.width, a.height)
+                        == (b.x_coordinate, b.y_coordinate, b.width, b.height);
+                    assert!(same || !overlaps(a, b), "{layout_type:?}");
+                    if same {
+                        assert!(a.width < 2 && a.height < 2, "{layout_type:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grid_fills_rows_first() {
+        let geometries = LayoutType::Grid.new().arrange(
+            &[1.0; 3],
+            1920,
+            1080,
+            &GapConfig::default(),
+            0.55,
+            1,
+            false,
+        );
+        let position = |i: usize| (geometries[i].x_coordinate, geometries[i].y_coordinate);
+
+        // Two columns on top, the third window below the first
+        assert_eq!(position(0).1, position(1).1);
+        assert!(position(0).0 < position(1).0);
+        assert_eq!(position(2).0, position(0).0);
+        assert!(position(2).1 > position(0).1);
+    }
+}
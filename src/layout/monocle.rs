@@ -0,0 +1,40 @@
+use super::{GapConfig, Layout, WindowGeometry, tiling::Tiling};
+pub struct Monocle;
+
+impl Layout for Monocle {
+    fn arrange(
+        &self,
+        client_factors: &[f32],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        _master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = client_factors.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let gap_values = Tiling::getgaps(gaps, window_count, smartgaps_enabled);
+
+        // Every window covers the same area, stacking decides which one is seen
+        let geometry = WindowGeometry {
+            x_coordinate: gap_values.outer_vertical as i32,
+            y_coordinate: gap_values.outer_horizontal as i32,
+            width: screen_width.saturating_sub(2 * gap_values.outer_vertical),
+            height: screen_height.saturating_sub(2 * gap_values.outer_horizontal),
+        };
+
+        vec![geometry; window_count]
+    }
+
+    fn symbol(&self, window_count: usize) -> String {
+        if window_count == 0 {
+            "[M]".into()
+        } else {
+            format!("[{window_count}]")
+        }
+    }
+}
@@ -1,23 +1,26 @@
 use super::{GapConfig, Layout, WindowGeometry};
-use smithay::desktop::Window;
 pub struct Tiling;
 
-struct GapValues {
-    outer_horizontal: u32,
-    outer_vertical: u32,
-    inner_horizontal: u32,
-    inner_vertical: u32,
+pub(super) struct GapValues {
+    pub outer_horizontal: u32,
+    pub outer_vertical: u32,
+    pub inner_horizontal: u32,
+    pub inner_vertical: u32,
 }
 
-struct FactValues {
-    master_facts: f32,
-    stack_facts: f32,
-    master_remainder: i32,
-    stack_remainder: i32,
+pub(super) struct FactValues {
+    pub master_facts: f32,
+    pub stack_facts: f32,
+    pub master_remainder: i32,
+    pub stack_remainder: i32,
 }
 
 impl Tiling {
-    fn getgaps(gaps: &GapConfig, window_count: usize, smartgaps_enabled: bool) -> GapValues {
+    pub(super) fn getgaps(
+        gaps: &GapConfig,
+        window_count: usize,
+        smartgaps_enabled: bool,
+    ) -> GapValues {
         let outer_enabled = if smartgaps_enabled && window_count == 1 {
             0
         } else {
@@ -33,30 +36,25 @@ impl Tiling {
         }
     }
 
-    fn getfacts(
-        window_count: usize,
+    pub(super) fn getfacts(
+        client_factors: &[f32],
         num_master: i32,
         master_size: i32,
         stack_size: i32,
     ) -> FactValues {
-        let num_master = num_master.max(0) as usize;
-        let master_facts = window_count.min(num_master) as f32;
-        let stack_facts = if window_count > num_master {
-            (window_count - num_master) as f32
-        } else {
-            0.0
-        };
-
-        let mut master_total = 0;
-        let mut stack_total = 0;
-
-        for i in 0..window_count {
-            if i < num_master {
-                master_total += (master_size as f32 / master_facts) as i32;
-            } else if stack_facts > 0.0 {
-                stack_total += (stack_size as f32 / stack_facts) as i32;
-            }
-        }
+        let num_master = (num_master.max(0) as usize).min(client_factors.len());
+        let (master_factors, stack_factors) = client_factors.split_at(num_master);
+        let master_facts: f32 = master_factors.iter().sum();
+        let stack_facts: f32 = stack_factors.iter().sum();
+
+        let master_total: i32 = master_factors
+            .iter()
+            .map(|&cfact| Self::share(master_size, cfact, master_facts))
+            .sum();
+        let stack_total: i32 = stack_factors
+            .iter()
+            .map(|&cfact| Self::share(stack_size, cfact, stack_facts))
+            .sum();
 
         FactValues {
             master_facts,
@@ -65,12 +63,17 @@ impl Tiling {
             stack_remainder: stack_size - stack_total,
         }
     }
+
+    /// The part of `size` a window with weight `cfact` gets out of `total`.
+    pub(super) fn share(size: i32, cfact: f32, total: f32) -> i32 {
+        (size as f32 * (cfact / total)) as i32
+    }
 }
 
 impl Layout for Tiling {
     fn arrange(
         &self,
-        windows: &[Window],
+        client_factors: &[f32],
         screen_width: u32,
         screen_height: u32,
         gaps: &super::GapConfig,
@@ -78,7 +81,7 @@ impl Layout for Tiling {
         num_master: i32,
         smartgaps_enabled: bool,
     ) -> Vec<super::WindowGeometry> {
-        let window_count = windows.len();
+        let window_count = client_factors.len();
         if window_count == 0 {
             return Vec::new();
         }
@@ -115,13 +118,13 @@ impl Layout for Tiling {
             stack_x = master_x + master_width + inner_gap_vertical as i32;
         }
 
-        let facts = Self::getfacts(window_count, num_master, master_height, stack_height);
+        let facts = Self::getfacts(client_factors, num_master, master_height, stack_height);
 
         let mut geometries = Vec::new();
 
-        for (i, _window) in windows.iter().enumerate() {
+        for (i, &cfact) in client_factors.iter().enumerate() {
             if i < num_master_usize {
-                let window_height = (master_height as f32 / facts.master_facts) as i32
+                let window_height = Self::share(master_height, cfact, facts.master_facts)
                     + if (i as i32) < facts.master_remainder {
                         1
                     } else {
@@ -138,7 +141,7 @@ impl Layout for Tiling {
                 master_y += window_height + inner_gap_horizontal as i32;
             } else {
                 let window_height = if facts.stack_facts > 0.0 {
-                    (stack_height as f32 / facts.stack_facts) as i32
+                    Self::share(stack_height, cfact, facts.stack_facts)
                         + if ((i - num_master_usize) as i32) < facts.stack_remainder {
                             1
                         } else {
@@ -161,4 +164,8 @@ impl Layout for Tiling {
 
         geometries
     }
+
+    fn symbol(&self, _window_count: usize) -> String {
+        "[]=".into()
+    }
 }
@@ -1,12 +1,20 @@
 pub mod action;
 pub mod backend;
+pub mod client;
+pub mod config;
 pub mod errors;
+pub mod focus;
 pub mod grabs;
 mod handlers;
 pub mod input;
+pub mod ipc;
+pub mod keybindings;
 pub mod layout;
 pub mod protocols;
+pub mod rules;
 pub mod state;
+pub mod switcher;
+pub mod workspace;
 
 pub use errors::{CompositorError, Result};
 pub use state::ProjectWC;
@@ -1,29 +1,49 @@
-use projectwc::{CompositorError, Result, state::ProjectWC};
+use projectwc::{CompositorError, Result, config::Config, ipc, state::ProjectWC};
 use smithay::reexports::{calloop::EventLoop, wayland_server::Display};
 
 fn main() -> Result<()> {
+    // `projectwc msg <command>` talks to an already running compositor
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "msg") {
+        let reply = ipc::send_command(&args[1..].join(" "))?;
+        println!("{reply}");
+        return Ok(());
+    }
+
     if let Ok(env_filter) = tracing_subscriber::EnvFilter::try_from_default_env() {
         tracing_subscriber::fmt().with_env_filter(env_filter).init();
     } else {
         tracing_subscriber::fmt().init();
     }
 
+    let config = Config::load().inspect_err(|err| tracing::error!("{err}"))?;
+
     let mut event_loop: EventLoop<ProjectWC> =
         EventLoop::try_new().map_err(|e| CompositorError::EventLoop(e.to_string()))?;
 
     let display = Display::new().map_err(|e| CompositorError::Backend(e.to_string()))?;
-    let mut state = ProjectWC::new(display, event_loop.handle(), event_loop.get_signal());
+    let mut state = ProjectWC::new(
+        display,
+        event_loop.handle(),
+        event_loop.get_signal(),
+        config,
+    );
 
     projectwc::backend::winit::init_winit(&mut event_loop, &mut state)?;
 
-    let spawn_cmd: Option<String> = std::env::args().nth(1);
+    let ipc_socket = ipc::init_ipc_listener(&event_loop.handle(), &state.socket_name)?;
+    // Set PROJECTWC_SOCKET for child processes
+    unsafe { std::env::set_var(ipc::SOCKET_ENV, &ipc_socket) };
+
+    let spawn_cmd: Option<&String> = args.first();
     if let Some(cmd) = spawn_cmd {
-        std::process::Command::new(&cmd).spawn().ok();
+        std::process::Command::new(cmd).spawn().ok();
     }
 
-    event_loop
+    let result = event_loop
         .run(None, &mut state, |_| {})
-        .map_err(|e| CompositorError::EventLoop(e.to_string()))?;
+        .map_err(|e| CompositorError::EventLoop(e.to_string()));
 
-    Ok(())
+    let _ = std::fs::remove_file(&ipc_socket);
+    result
 }
@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use smithay::wayland::{
+    compositor,
+    shell::xdg::{ToplevelSurface, XdgToplevelSurfaceData},
+};
+
+/// Settings for windows matching `app_id` and `title`, from the config's
+/// `[[rules]]` list. A rule without either matches every window.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowRule {
+    /// Matches the app id exactly.
+    pub app_id: Option<String>,
+    /// Matches any title containing this.
+    pub title: Option<String>,
+    pub decorations: Option<DecorationMode>,
+}
+
+/// Who draws a window's title bar and frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecorationMode {
+    Client,
+    Server,
+}
+
+impl WindowRule {
+    pub fn matches(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
+        let app_id_matches = self
+            .app_id
+            .as_deref()
+            .is_none_or(|wanted| app_id == Some(wanted));
+        let title_matches = self
+            .title
+            .as_deref()
+            .is_none_or(|wanted| title.is_some_and(|title| title.contains(wanted)));
+        app_id_matches && title_matches
+    }
+}
+
+/// The first setting picked by any rule matching `toplevel`, in config order.
+pub fn rule_setting<T>(
+    rules: &[WindowRule],
+    toplevel: &ToplevelSurface,
+    setting: impl Fn(&WindowRule) -> Option<T>,
+) -> Option<T> {
+    let (title, app_id) = title_and_app_id(toplevel);
+    rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref()))
+        .find_map(setting)
+}
+
+/// The title and app id `toplevel` set for itself, if any.
+pub fn title_and_app_id(toplevel: &ToplevelSurface) -> (Option<String>, Option<String>) {
+    compositor::with_states(toplevel.wl_surface(), |states| {
+        states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .map(|data| {
+                let data = data.lock().unwrap();
+                (data.title.clone(), data.app_id.clone())
+            })
+            .unwrap_or_default()
+    })
+}
@@ -1,30 +1,40 @@
 use smithay::{
-    desktop::{PopupManager, Space, Window, WindowSurfaceType},
+    desktop::{PopupManager, Space, Window, WindowSurfaceType, layer_map_for_output},
     input::{Seat, SeatState, pointer::PointerHandle},
     reexports::{
-        calloop::{Interest, LoopHandle, LoopSignal, Mode, PostAction, generic::Generic},
+        calloop::{
+            Interest, LoopHandle, LoopSignal, Mode, PostAction, RegistrationToken, generic::Generic,
+        },
         wayland_server::{
             Display, DisplayHandle,
             backend::{ClientData, ClientId, DisconnectReason},
             protocol::wl_surface::WlSurface,
         },
     },
-    utils::{Logical, Point},
+    utils::{Logical, Point, Rectangle},
     wayland::{
         compositor::{CompositorClientState, CompositorState},
         output::OutputManagerState,
         selection::{data_device::DataDeviceState, primary_selection::PrimarySelectionState},
-        shell::{wlr_layer::WlrLayerShellState, xdg::XdgShellState},
+        shell::{
+            wlr_layer::WlrLayerShellState,
+            xdg::{XdgShellState, decoration::XdgDecorationState},
+        },
         shm::ShmState,
         socket::ListeningSocketSource,
+        xdg_activation::XdgActivationState,
     },
 };
 use std::{ffi::OsString, sync::Arc};
 
 use crate::{
     CompositorError,
-    layout::{GapConfig, LayoutBox, LayoutType},
+    action::Direction,
+    client::Client,
+    config::Config,
     protocols::wlr_screencopy::{Screencopy, ScreencopyManagerState},
+    switcher::Switcher,
+    workspace::{TAG_COUNT, Workspace},
 };
 
 pub struct ProjectWC {
@@ -34,7 +44,15 @@ pub struct ProjectWC {
 
     pub space: Space<Window>,
     pub seat: Seat<Self>,
-    pub layout: LayoutBox,
+    pub config: Config,
+    /// Every managed window, in tiling and focus cycling order. Stacking is
+    /// up to `space` and never changes this order.
+    pub clients: Vec<Client>,
+    /// Per-tag layout state, indexed by tag number.
+    pub workspaces: Vec<Workspace>,
+    /// Bitmask of the tags currently viewed.
+    pub tagset: u32,
+    pub previous_tagset: u32,
     pub socket_name: OsString,
     pub start_time: std::time::Instant,
 
@@ -49,9 +67,16 @@ pub struct ProjectWC {
     pub primary_selection_state: PrimarySelectionState,
     pub layer_shell_state: WlrLayerShellState,
     pub screencopy_state: ScreencopyManagerState,
+    pub xdg_activation_state: XdgActivationState,
+    pub xdg_decoration_state: XdgDecorationState,
 
     pub pointer_location: Point<f64, Logical>,
     pub pending_screencopy: Option<Screencopy>,
+    /// Tile highlighted while a tiled window is dragged over it.
+    pub drop_preview: Option<Rectangle<i32, Logical>>,
+    pub switcher: Option<Switcher>,
+    /// Window the pointer rests on, focused once its timer fires.
+    pub sloppy_focus: Option<(Window, RegistrationToken)>,
 }
 
 impl ProjectWC {
@@ -59,6 +84,7 @@ impl ProjectWC {
         display: Display<Self>,
         loop_handle: LoopHandle<'static, ProjectWC>,
         loop_signal: LoopSignal,
+        config: Config,
     ) -> Self {
         let start_time = std::time::Instant::now();
 
@@ -74,6 +100,8 @@ impl ProjectWC {
         let primary_selection_state = PrimarySelectionState::new::<Self>(&display_handle);
         let layer_shell_state = WlrLayerShellState::new::<Self>(&display_handle);
         let screencopy_state = ScreencopyManagerState::new::<Self, _>(&display_handle, |_| true);
+        let xdg_activation_state = XdgActivationState::new::<Self>(&display_handle);
+        let xdg_decoration_state = XdgDecorationState::new::<Self>(&display_handle);
         let mut seat_state = SeatState::new();
 
         let mut seat = seat_state.new_wl_seat(&display_handle, "winit");
@@ -85,8 +113,9 @@ impl ProjectWC {
 
         let socket_name = init_wayland_listener(display, &loop_handle);
 
-        // TODO: Get a brain
-        let layout = LayoutType::from_str("tiling").unwrap().new();
+        let workspaces = (0..TAG_COUNT)
+            .map(|_| Workspace::new(&config.layout))
+            .collect();
 
         Self {
             display_handle,
@@ -94,8 +123,12 @@ impl ProjectWC {
             loop_signal,
 
             space,
-            layout,
             seat,
+            config,
+            clients: Vec::new(),
+            workspaces,
+            tagset: 1,
+            previous_tagset: 1,
             socket_name,
             start_time,
 
@@ -109,15 +142,49 @@ impl ProjectWC {
             primary_selection_state,
             layer_shell_state,
             screencopy_state,
+            xdg_activation_state,
+            xdg_decoration_state,
 
             pointer_location: Point::from((0.0, 0.0)),
             pending_screencopy: None,
+            drop_preview: None,
+            switcher: None,
+            sloppy_focus: None,
         }
     }
 
+    /// Arranges the tiled windows on the viewed tags around any panels, maps
+    /// the floating ones where they were left, stretches fullscreen and
+    /// maximized ones and unmaps all others.
     pub fn apply_layout(&mut self) -> Result<(), CompositorError> {
-        let windows: Vec<smithay::desktop::Window> = self.space.elements().cloned().collect();
-        if windows.is_empty() {
+        for client in &mut self.clients {
+            // Remember where a floating window was, in case it gets unmapped
+            if client.floating
+                && !client.is_stretched()
+                && let Some(location) = self.space.element_location(&client.window)
+            {
+                client.float_location = Some(location);
+            }
+            if !client.is_visible_on(self.tagset) {
+                self.space.unmap_elem(&client.window);
+            }
+        }
+
+        let (windows, client_factors): (Vec<Window>, Vec<f32>) = self
+            .clients
+            .iter()
+            .filter(|client| client.is_visible_on(self.tagset) && client.is_tiled())
+            .map(|client| (client.window.clone(), client.cfact))
+            .unzip();
+        let stretched: Vec<(Window, bool)> = self
+            .clients
+            .iter()
+            .filter(|client| client.is_visible_on(self.tagset) && client.is_stretched())
+            .map(|client| (client.window.clone(), client.fullscreen))
+            .collect();
+        let workspace = self.active_workspace();
+        if windows.is_empty() && stretched.is_empty() {
+            self.restack();
             return Ok(());
         }
 
@@ -133,49 +200,221 @@ impl ProjectWC {
             .output_geometry(&output)
             .ok_or_else(|| CompositorError::Backend("no output geometry".into()))?;
 
-        let gaps = GapConfig {
-            outer_horizontal: 20,
-            outer_vertical: 20,
-            inner_horizontal: 10,
-            inner_vertical: 10,
+        // The part of the output not reserved by panels
+        let usable_geo = {
+            let zone = layer_map_for_output(&output).non_exclusive_zone();
+            Rectangle::new(out_geo.loc + zone.loc, zone.size)
         };
 
-        let master_factor: f32 = 0.55;
-        let num_master: i32 = 1;
-        let smartgaps_enabled: bool = true;
-
-        let geometries = self.layout.arrange(
-            &windows,
-            out_geo.size.w as u32,
-            out_geo.size.h as u32,
-            &gaps,
-            master_factor,
-            num_master,
-            smartgaps_enabled,
+        let geometries = workspace.layout.arrange(
+            &client_factors,
+            usable_geo.size.w as u32,
+            usable_geo.size.h as u32,
+            &self.config.gaps,
+            workspace.master_factor,
+            workspace.num_master,
+            self.config.layout.smartgaps,
         );
 
         for (window, geom) in windows.into_iter().zip(geometries.into_iter()) {
-            let loc = Point::<i32, Logical>::from((
-                out_geo.loc.x + geom.x_coordinate,
-                out_geo.loc.y + geom.y_coordinate,
-            ));
+            let tile = Rectangle::new(
+                (
+                    usable_geo.loc.x + geom.x_coordinate,
+                    usable_geo.loc.y + geom.y_coordinate,
+                )
+                    .into(),
+                (geom.width as i32, geom.height as i32).into(),
+            );
+            // The border is drawn around the window, inside the tile
+            let area = self.inside_border(tile);
+
+            if let Some(toplevel) = window.toplevel() {
+                toplevel.with_pending_state(|state| {
+                    state.size = Some(area.size);
+                });
+                toplevel.send_pending_configure();
+            }
+
+            self.space.map_element(window, area.loc, false);
+        }
+
+        for (window, fullscreen) in stretched {
+            let area = if fullscreen {
+                out_geo
+            } else {
+                self.inside_border(usable_geo)
+            };
 
             if let Some(toplevel) = window.toplevel() {
                 toplevel.with_pending_state(|state| {
-                    state.size = Some((geom.width as i32, geom.height as i32).into());
+                    state.size = Some(area.size);
                 });
                 toplevel.send_pending_configure();
             }
 
-            self.space.map_element(window, loc, false);
+            self.space.map_element(window, area.loc, false);
         }
 
+        self.restack();
         Ok(())
     }
 
+    /// `rect` minus the configured border width on every side.
+    fn inside_border(&self, rect: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let width = self.config.border.width as i32;
+        Rectangle::new(
+            rect.loc + Point::from((width, width)),
+            (
+                (rect.size.w - 2 * width).max(1),
+                (rect.size.h - 2 * width).max(1),
+            )
+                .into(),
+        )
+    }
+
+    /// Colour of the border around `window`, `None` if it has none.
+    pub fn border_color(&self, window: &Window) -> Option<[f32; 4]> {
+        let client = self.client(window)?;
+        if client.fullscreen || self.config.border.width == 0 {
+            return None;
+        }
+
+        let border = &self.config.border;
+        let color = if self.focused_window().as_ref() == Some(window) {
+            border.focused
+        } else if client.urgent {
+            border.urgent
+        } else {
+            border.unfocused
+        };
+        Some(color.0)
+    }
+
+    /// Stacks the visible windows as tiled, then floating and maximized, then
+    /// fullscreen, with the focused window on top of its group.
+    pub fn restack(&mut self) {
+        let focused = self.focused_window();
+        let focused_level = focused
+            .as_ref()
+            .and_then(|window| self.client(window))
+            .map(Client::stacking_level);
+
+        for level in 0..=2 {
+            // Tiled windows stay where the layout put them
+            if level > 0 {
+                for client in &self.clients {
+                    let location = self
+                        .space
+                        .element_location(&client.window)
+                        .or(client.float_location);
+                    if client.stacking_level() == level
+                        && client.is_visible_on(self.tagset)
+                        && let Some(location) = location
+                    {
+                        self.space
+                            .map_element(client.window.clone(), location, false);
+                    }
+                }
+            }
+
+            // Overlapping layouts like monocle show whichever window has focus
+            if focused_level == Some(level)
+                && let Some(window) = &focused
+            {
+                self.space.raise_element(window, false);
+            }
+        }
+    }
+
+    pub fn client(&self, window: &Window) -> Option<&Client> {
+        self.clients.iter().find(|client| &client.window == window)
+    }
+
+    /// Whether the layout decides where `window` goes.
+    pub fn is_tiled(&self, window: &Window) -> bool {
+        self.clients
+            .iter()
+            .any(|client| &client.window == window && client.is_tiled())
+    }
+
+    pub fn is_fullscreen(&self, window: &Window) -> bool {
+        self.clients
+            .iter()
+            .any(|client| &client.window == window && client.fullscreen)
+    }
+
+    /// Whether `window` is fullscreen or maximized, so its size is fixed.
+    pub fn is_stretched(&self, window: &Window) -> bool {
+        self.client(window).is_some_and(Client::is_stretched)
+    }
+
+    /// Whether the focused window covers the output, which hides bars.
+    pub fn is_fullscreen_focused(&self) -> bool {
+        self.focused_window()
+            .is_some_and(|window| self.is_fullscreen(&window))
+    }
+
+    pub fn is_floating(&self, window: &Window) -> bool {
+        self.clients
+            .iter()
+            .any(|client| &client.window == window && client.floating)
+    }
+
+    /// Re-reads the config file and applies whatever changed.
+    ///
+    /// An invalid file is logged and the running config is kept.
+    pub fn reload_config(&mut self) -> Result<(), CompositorError> {
+        let config = Config::load().inspect_err(|err| {
+            tracing::error!("keeping previous config: {err}");
+        })?;
+
+        // Only touch the workspace settings that changed in the file, so
+        // runtime adjustments to the others survive a reload.
+        let old = &self.config.layout;
+        let new = &config.layout;
+        for workspace in &mut self.workspaces {
+            if new.default != old.default {
+                workspace.set_layout(new.default);
+            }
+            if new.master_factor != old.master_factor {
+                workspace.master_factor = new.master_factor;
+            }
+            if new.num_master != old.num_master {
+                workspace.num_master = new.num_master;
+            }
+        }
+
+        // Keybindings are looked up from the config on every key press, so
+        // swapping the config is all it takes to rebind them.
+        self.config = config;
+
+        // Window rules are otherwise only applied when a window maps
+        let toplevels: Vec<_> = self
+            .clients
+            .iter()
+            .filter_map(|client| client.window.toplevel().cloned())
+            .collect();
+        for toplevel in &toplevels {
+            self.update_decoration_mode(toplevel);
+        }
+
+        tracing::info!("config reloaded");
+        self.apply_layout()
+    }
+
+    /// The window holding keyboard focus, if any.
+    pub fn focused_window(&self) -> Option<Window> {
+        let keyboard = self.seat.get_keyboard()?;
+        keyboard
+            .current_focus()
+            .and_then(|surface| self.window_for_surface(&surface))
+    }
+
+    /// Finds the window for `surface` on any tag, visible or not.
     pub fn window_for_surface(&self, surface: &WlSurface) -> Option<Window> {
-        self.space
-            .elements()
+        self.clients
+            .iter()
+            .map(|client| &client.window)
             .find(|window| {
                 window
                     .toplevel()
@@ -190,6 +429,70 @@ impl ProjectWC {
             .map(|(w, p)| (w.clone(), p))
     }
 
+    /// The tiled window shown at `location`, looking past `except`.
+    ///
+    /// A floating window on top hides the tiles beneath it.
+    pub fn tiled_window_at(
+        &self,
+        location: Point<f64, Logical>,
+        except: &Window,
+    ) -> Option<Window> {
+        self.space
+            .elements()
+            .rev()
+            .filter(|window| *window != except)
+            .find(|window| {
+                self.space
+                    .element_geometry(window)
+                    .is_some_and(|geo| geo.to_f64().contains(location))
+            })
+            .filter(|window| self.is_tiled(window))
+            .cloned()
+    }
+
+    /// The visible window closest to `window` in `direction`.
+    ///
+    /// Windows on the same output come first, so focus only falls over to an
+    /// adjacent output at the edge of this one. Among the rest, windows that
+    /// line up with `window` beat ones that are merely closer.
+    pub fn window_in_direction(&self, window: &Window, direction: Direction) -> Option<Window> {
+        let from = self.space.element_geometry(window)?;
+        let center = |geo: Rectangle<i32, Logical>| geo.loc + geo.size.downscale(2).to_point();
+        let from_center = center(from);
+        let output_geo = self
+            .space
+            .outputs()
+            .filter_map(|output| self.space.output_geometry(output))
+            .find(|geo| geo.contains(from_center));
+
+        self.visible_windows()
+            .into_iter()
+            .filter(|other| other != window)
+            .filter_map(|other| {
+                let geo = self.space.element_geometry(&other)?;
+                let to = center(geo);
+                let same_rows =
+                    geo.loc.y < from.loc.y + from.size.h && from.loc.y < geo.loc.y + geo.size.h;
+                let same_columns =
+                    geo.loc.x < from.loc.x + from.size.w && from.loc.x < geo.loc.x + geo.size.w;
+                // Distance along the direction and across it
+                let (along, across, lined_up) = match direction {
+                    Direction::Left => (from_center.x - to.x, to.y - from_center.y, same_rows),
+                    Direction::Right => (to.x - from_center.x, to.y - from_center.y, same_rows),
+                    Direction::Up => (from_center.y - to.y, to.x - from_center.x, same_columns),
+                    Direction::Down => (to.y - from_center.y, to.x - from_center.x, same_columns),
+                };
+                if along <= 0 {
+                    return None;
+                }
+
+                let other_output = !output_geo.is_some_and(|out| out.contains(to));
+                Some(((other_output, !lined_up, along, across.abs()), other))
+            })
+            .min_by_key(|(score, _)| *score)
+            .map(|(_, other)| other)
+    }
+
     pub fn surface_under_pointer(&self) -> Option<(WlSurface, Point<f64, Logical>)> {
         let position = self.pointer_location;
         self.space
@@ -0,0 +1,158 @@
+use smithay::{
+    desktop::Window,
+    input::keyboard::GrabStartData as KeyboardGrabStartData,
+    utils::{Logical, Rectangle, SERIAL_COUNTER},
+};
+
+use crate::{ProjectWC, backend::label::LINE_HEIGHT, grabs::switcher_grab::SwitcherGrab};
+
+/// Largest side of a thumbnail in the switcher overlay.
+const THUMBNAIL_SIZE: i32 = 240;
+/// Space around and between thumbnails.
+const PADDING: i32 = 16;
+/// Room under each thumbnail for the window's title and app id.
+const LABEL_HEIGHT: i32 = 2 * LINE_HEIGHT;
+
+/// Alt-Tab style window switcher, open while its modifier is held.
+pub struct Switcher {
+    /// The visible windows, most recently focused first.
+    pub windows: Vec<Window>,
+    pub selected: usize,
+}
+
+impl Switcher {
+    fn step(&mut self, forward: bool) {
+        let len = self.windows.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % len
+        } else {
+            (self.selected + len - 1) % len
+        };
+    }
+
+    /// The overlay's background, centered on `output_geo`.
+    pub fn panel(&self, output_geo: Rectangle<i32, Logical>) -> Rectangle<i32, Logical> {
+        let size = self.thumbnail_size(output_geo);
+        let count = self.windows.len() as i32;
+        let width = count * (size + PADDING) + PADDING;
+        let height = size + LABEL_HEIGHT + 3 * PADDING;
+
+        Rectangle::new(
+            (
+                output_geo.loc.x + (output_geo.size.w - width) / 2,
+                output_geo.loc.y + (output_geo.size.h - height) / 2,
+            )
+                .into(),
+            (width, height).into(),
+        )
+    }
+
+    /// Where each window's thumbnail goes, in the same order as `windows`.
+    pub fn slots(&self, output_geo: Rectangle<i32, Logical>) -> Vec<Rectangle<i32, Logical>> {
+        let size = self.thumbnail_size(output_geo);
+        let panel = self.panel(output_geo);
+
+        (0..self.windows.len() as i32)
+            .map(|i| {
+                Rectangle::new(
+                    (
+                        panel.loc.x + PADDING + i * (size + PADDING),
+                        panel.loc.y + PADDING,
+                    )
+                        .into(),
+                    (size, size).into(),
+                )
+            })
+            .collect()
+    }
+
+    /// Where each window's title and app id go, under its thumbnail.
+    pub fn labels(&self, output_geo: Rectangle<i32, Logical>) -> Vec<Rectangle<i32, Logical>> {
+        self.slots(output_geo)
+            .into_iter()
+            .map(|slot| {
+                Rectangle::new(
+                    (slot.loc.x, slot.loc.y + slot.size.h + PADDING).into(),
+                    (slot.size.w, LABEL_HEIGHT).into(),
+                )
+            })
+            .collect()
+    }
+
+    /// Shrinks thumbnails so that a long row still fits on the output.
+    fn thumbnail_size(&self, output_geo: Rectangle<i32, Logical>) -> i32 {
+        let count = self.windows.len().max(1) as i32;
+        let fitting = (output_geo.size.w - PADDING) / count - PADDING;
+        fitting.clamp(1, THUMBNAIL_SIZE)
+    }
+}
+
+impl ProjectWC {
+    /// Opens the switcher on the window focused before the current one, or
+    /// moves its selection on if it is open already.
+    pub fn switch_window(&mut self, forward: bool) {
+        if let Some(switcher) = &mut self.switcher {
+            switcher.step(forward);
+            return;
+        }
+
+        let windows = self.mru_windows();
+        if windows.len() < 2 {
+            return;
+        }
+
+        let selected = if forward { 1 } else { windows.len() - 1 };
+        self.switcher = Some(Switcher { windows, selected });
+
+        let keyboard = self.seat.get_keyboard().unwrap();
+        let start_data = KeyboardGrabStartData {
+            focus: keyboard.current_focus(),
+        };
+        keyboard.set_grab(
+            self,
+            SwitcherGrab::new(start_data),
+            SERIAL_COUNTER.next_serial(),
+        );
+    }
+
+    /// Closes the switcher and focuses the window it had selected.
+    pub fn finish_switch(&mut self) {
+        let Some(switcher) = self.switcher.take() else {
+            return;
+        };
+
+        let window = switcher.windows.get(switcher.selected);
+        if let Some(window) = window.filter(|w| self.client(w).is_some()) {
+            self.focus_window_by_keyboard(window);
+        }
+    }
+
+    /// The visible windows, most recently focused first. Windows that never
+    /// had focus follow in tiling order.
+    pub fn mru_windows(&self) -> Vec<Window> {
+        let visible = self.visible_windows();
+        let mut windows: Vec<Window> = self
+            .active_workspace()
+            .focus_history
+            .iter()
+            .rev()
+            .filter(|window| visible.contains(window))
+            .cloned()
+            .collect();
+
+        // A window focused from another tag may not be in this history yet
+        if let Some(focused) = self.focused_window() {
+            windows.retain(|window| window != &focused);
+            windows.insert(0, focused);
+        }
+        for window in visible {
+            if !windows.contains(&window) {
+                windows.push(window);
+            }
+        }
+        windows
+    }
+}
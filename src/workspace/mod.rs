@@ -1,23 +1,437 @@
-use smithay::{
-    desktop::{Space, Window},
-    input::{pointer::PointerHandle, Seat, SeatState},
-    reexports::{
-        calloop::LoopHandle,
-        wayland_server::{
-            backend::{ClientData, ClientId, DisconnectReason},
-            Display, DisplayHandle,
-        },
-    },
-    utils::{Logical, Point},
-    wayland::{
-        compositor::{CompositorClientState, CompositorState},
-        output::OutputManagerState,
-        selection::data_device::DataDeviceState,
-        shell::xdg::XdgShellState,
-        shm::ShmState,
-        socket::ListeningSocketSource,
-    },
+use serde::{Deserialize, Deserializer, de::Error as _};
+use smithay::{desktop::Window, reexports::wayland_protocols::xdg::shell::server::xdg_toplevel};
+
+use crate::{
+    ProjectWC,
+    action::Direction,
+    client::Client,
+    config::LayoutConfig,
+    layout::{LayoutBox, LayoutType},
 };
+
+/// Number of tags, addressed as 1 through 9 from keybindings.
+pub const TAG_COUNT: usize = 9;
+
+/// Mask with every tag set.
+pub const ALL_TAGS: u32 = (1 << TAG_COUNT) - 1;
+
+/// A set of tags, written in the config as a tag number or a list of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagMask(pub u32);
+
+impl TagMask {
+    /// Mask for a single tag, numbered from 1 as on the keyboard.
+    pub fn tag(num: usize) -> Self {
+        Self(1 << (num - 1))
+    }
+}
+
+impl<'de> Deserialize<'de> for TagMask {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Tags {
+            One(usize),
+            Many(Vec<usize>),
+        }
+
+        let tags = match Tags::deserialize(deserializer)? {
+            Tags::One(num) => vec![num],
+            Tags::Many(nums) => nums,
+        };
+
+        let mut mask = 0;
+        for num in tags {
+            if !(1..=TAG_COUNT).contains(&num) {
+                return Err(D::Error::custom(format!(
+                    "tag must be between 1 and {TAG_COUNT}, got {num}"
+                )));
+            }
+            mask |= TagMask::tag(num).0;
+        }
+
+        if mask == 0 {
+            return Err(D::Error::custom("at least one tag is required"));
+        }
+        Ok(Self(mask))
+    }
+}
+
+/// Layout state kept per tag, like dwm's pertag patch.
+///
+/// When several tags are viewed at once the lowest one's state is used.
 pub struct Workspace {
-    layout: Layout,
+    pub layout_type: LayoutType,
+    pub layout: LayoutBox,
+    pub master_factor: f32,
+    pub num_master: i32,
+    /// Windows focused while this tag was active, most recent last.
+    pub focus_history: Vec<Window>,
+}
+
+impl Workspace {
+    pub fn new(config: &LayoutConfig) -> Self {
+        Self {
+            layout_type: config.default,
+            layout: config.default.new(),
+            master_factor: config.master_factor,
+            num_master: config.num_master,
+            focus_history: Vec::new(),
+        }
+    }
+
+    pub fn set_layout(&mut self, layout_type: LayoutType) {
+        self.layout_type = layout_type;
+        self.layout = layout_type.new();
+    }
+
+    pub fn set_master_factor(&mut self, master_factor: f32) {
+        self.master_factor = master_factor.clamp(0.05, 0.95);
+    }
+}
+
+impl ProjectWC {
+    pub fn active_workspace(&self) -> &Workspace {
+        &self.workspaces[self.active_workspace_index()]
+    }
+
+    pub fn active_workspace_mut(&mut self) -> &mut Workspace {
+        let idx = self.active_workspace_index();
+        &mut self.workspaces[idx]
+    }
+
+    fn active_workspace_index(&self) -> usize {
+        (self.tagset.trailing_zeros() as usize).min(TAG_COUNT - 1)
+    }
+
+    /// Windows shown by the current view, in tiling order.
+    pub fn visible_windows(&self) -> Vec<Window> {
+        self.clients
+            .iter()
+            .filter(|client| client.is_visible_on(self.tagset))
+            .map(|client| client.window.clone())
+            .collect()
+    }
+
+    /// The visible windows placed by the layout, master first.
+    pub fn tiled_windows(&self) -> Vec<Window> {
+        self.clients
+            .iter()
+            .filter(|client| client.is_visible_on(self.tagset) && client.is_tiled())
+            .map(|client| client.window.clone())
+            .collect()
+    }
+
+    /// Switches the active workspace to `layout_type` and re-arranges.
+    pub fn set_layout(&mut self, layout_type: LayoutType) {
+        self.active_workspace_mut().set_layout(layout_type);
+        self.apply_layout().ok();
+
+        let workspace = self.active_workspace();
+        let symbol = workspace.layout.symbol(self.visible_windows().len());
+        tracing::debug!(?layout_type, symbol, "Layout changed");
+    }
+
+    /// Switches the active workspace to the next layout in
+    /// [`LayoutType::ALL`].
+    pub fn cycle_layout(&mut self) {
+        let next = self.active_workspace().layout_type.next();
+        self.set_layout(next);
+    }
+
+    /// Grows or shrinks the master area of the active workspace, like dwm's
+    /// `setmfact`.
+    pub fn inc_master_factor(&mut self, delta: f32) {
+        let workspace = self.active_workspace_mut();
+        workspace.set_master_factor(workspace.master_factor + delta);
+        self.apply_layout().ok();
+    }
+
+    /// Changes how many windows share the master area, like dwm's
+    /// `incnmaster`.
+    pub fn inc_num_master(&mut self, delta: i32) {
+        let workspace = self.active_workspace_mut();
+        workspace.num_master = workspace.num_master.saturating_add(delta).max(0);
+        self.apply_layout().ok();
+    }
+
+    /// Puts the active workspace's master factor back to the configured one.
+    pub fn reset_master_factor(&mut self) {
+        self.active_workspace_mut().master_factor = self.config.layout.master_factor;
+        self.apply_layout().ok();
+    }
+
+    /// Puts the active workspace's master count back to the configured one.
+    pub fn reset_num_master(&mut self) {
+        self.active_workspace_mut().num_master = self.config.layout.num_master;
+        self.apply_layout().ok();
+    }
+
+    /// Changes the size weight of `window`, like dwm's `setcfact`.
+    pub fn inc_window_factor(&mut self, window: &Window, delta: f32) {
+        let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) else {
+            return;
+        };
+
+        client.set_cfact(client.cfact + delta);
+        self.apply_layout().ok();
+    }
+
+    pub fn reset_window_factor(&mut self, window: &Window) {
+        let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) else {
+            return;
+        };
+
+        client.cfact = 1.0;
+        self.apply_layout().ok();
+    }
+
+    /// Takes `window` out of the layout where it is, or puts it back in.
+    pub fn toggle_floating(&mut self, window: &Window) {
+        let floating = self.is_floating(window);
+        self.set_floating(window, !floating);
+    }
+
+    pub fn set_floating(&mut self, window: &Window, floating: bool) {
+        let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) else {
+            return;
+        };
+
+        client.floating = floating;
+        if floating {
+            client.float_location = self.space.element_location(window);
+        }
+        self.apply_layout().ok();
+    }
+
+    pub fn toggle_fullscreen(&mut self, window: &Window) {
+        let fullscreen = self.is_fullscreen(window);
+        self.set_fullscreen(window, !fullscreen);
+    }
+
+    /// Stretches `window` over the output, or puts it back where it was.
+    pub fn set_fullscreen(&mut self, window: &Window, fullscreen: bool) {
+        self.set_stretched(window, |client| client.fullscreen = fullscreen);
+    }
+
+    /// Stretches `window` over the area left free by panels, or puts it back
+    /// where it was. Maximizing a tiled window overrides the layout for it
+    /// until it is unmaximized.
+    pub fn set_maximized(&mut self, window: &Window, maximized: bool) {
+        self.set_stretched(window, |client| client.maximized = maximized);
+    }
+
+    /// Applies a fullscreen or maximized change to `window`.
+    ///
+    /// The geometry the window had before being stretched is saved and
+    /// restored once it is neither fullscreen nor maximized anymore.
+    fn set_stretched(&mut self, window: &Window, update: impl FnOnce(&mut Client)) {
+        let geometry = self.space.element_geometry(window);
+        let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) else {
+            return;
+        };
+
+        let was_stretched = client.is_stretched();
+        update(client);
+        let (fullscreen, maximized) = (client.fullscreen, client.maximized);
+
+        // Tiled windows get their old place back from the layout
+        let restore = match (was_stretched, client.is_stretched()) {
+            (false, true) => {
+                client.saved_geometry = geometry;
+                None
+            }
+            (true, false) => client.saved_geometry.take().filter(|_| client.floating),
+            _ => None,
+        };
+
+        let Some(toplevel) = window.toplevel() else {
+            return;
+        };
+        toplevel.with_pending_state(|state| {
+            for (xdg_state, set) in [
+                (xdg_toplevel::State::Fullscreen, fullscreen),
+                (xdg_toplevel::State::Maximized, maximized),
+            ] {
+                if set {
+                    state.states.set(xdg_state);
+                } else {
+                    state.states.unset(xdg_state);
+                }
+            }
+            if let Some(saved) = restore {
+                state.size = Some(saved.size);
+            }
+        });
+        if let Some(saved) = restore {
+            self.space.map_element(window.clone(), saved.loc, false);
+        }
+
+        self.apply_layout().ok();
+        // The layout doesn't configure floating windows, and the initial
+        // configure waits for the first commit
+        if toplevel.is_initial_configure_sent() {
+            toplevel.send_pending_configure();
+        }
+    }
+
+    /// Trades the places of two windows in the tiling order.
+    pub fn swap_windows(&mut self, a: &Window, b: &Window) {
+        let position = |window: &Window| self.clients.iter().position(|c| &c.window == window);
+        let (Some(a), Some(b)) = (position(a), position(b)) else {
+            return;
+        };
+
+        self.clients.swap(a, b);
+        self.apply_layout().ok();
+    }
+
+    /// Swaps `window` with the master, like dwm's `zoom`. The master itself
+    /// swaps with the window after it instead.
+    pub fn zoom(&mut self, window: &Window) {
+        let tiled = self.tiled_windows();
+        let target = match tiled.iter().position(|w| w == window) {
+            Some(0) => tiled.get(1),
+            Some(_) => tiled.first(),
+            None => return,
+        };
+
+        if let Some(target) = target {
+            self.swap_windows(window, target);
+        }
+    }
+
+    /// Moves `window` `offset` places along the tiling order, wrapping
+    /// around at either end.
+    pub fn move_window(&mut self, window: &Window, offset: isize) {
+        let tiled = self.tiled_windows();
+        let Some(position) = tiled.iter().position(|w| w == window) else {
+            return;
+        };
+
+        let target = (position as isize + offset).rem_euclid(tiled.len() as isize);
+        self.swap_windows(window, &tiled[target as usize]);
+    }
+
+    /// Swaps `window` with the nearest tile in `direction`. Floating windows
+    /// are moved with the mouse instead.
+    pub fn move_in_direction(&mut self, window: &Window, direction: Direction) {
+        if !self.is_tiled(window) {
+            return;
+        }
+
+        if let Some(target) = self
+            .window_in_direction(window, direction)
+            .filter(|target| self.is_tiled(target))
+        {
+            self.swap_windows(window, &target);
+        }
+    }
+
+    /// Swaps `window` with the tile at `index` in the tiling order, where the
+    /// master is 0.
+    pub fn swap_with(&mut self, window: &Window, index: usize) {
+        let tiled = self.tiled_windows();
+        if tiled.contains(window)
+            && let Some(target) = tiled.get(index)
+        {
+            self.swap_windows(window, target);
+        }
+    }
+
+    /// Views exactly the tags in `tagset`, remembering the current view for
+    /// [`ProjectWC::view_previous`].
+    pub fn view(&mut self, tagset: u32) {
+        let tagset = tagset & ALL_TAGS;
+        if tagset == 0 || tagset == self.tagset {
+            return;
+        }
+
+        self.previous_tagset = self.tagset;
+        self.tagset = tagset;
+        self.arrange_and_refocus();
+    }
+
+    /// Adds or removes the tags in `mask` from the view.
+    pub fn toggle_view(&mut self, mask: u32) {
+        let tagset = (self.tagset ^ mask) & ALL_TAGS;
+        if tagset == 0 {
+            return;
+        }
+
+        self.tagset = tagset;
+        self.arrange_and_refocus();
+    }
+
+    /// Switches back to the previously viewed tags.
+    pub fn view_previous(&mut self) {
+        std::mem::swap(&mut self.tagset, &mut self.previous_tagset);
+        self.arrange_and_refocus();
+    }
+
+    /// Puts `window` on exactly the tags in `mask`.
+    pub fn tag_window(&mut self, window: &Window, mask: u32) {
+        self.set_window_tags(window, |_| mask);
+    }
+
+    /// Adds or removes `window` from the tags in `mask`.
+    pub fn toggle_window_tag(&mut self, window: &Window, mask: u32) {
+        self.set_window_tags(window, |tags| tags ^ mask);
+    }
+
+    fn set_window_tags(&mut self, window: &Window, tags: impl FnOnce(u32) -> u32) {
+        let Some(client) = self.clients.iter_mut().find(|c| &c.window == window) else {
+            return;
+        };
+
+        // A window always stays on at least one tag
+        let tags = tags(client.tags) & ALL_TAGS;
+        if tags == 0 {
+            return;
+        }
+
+        client.tags = tags;
+        self.arrange_and_refocus();
+    }
+
+    /// Re-layouts and moves keyboard focus off a window that is no longer
+    /// visible. A pending sloppy focus change would undo that, so it goes.
+    fn arrange_and_refocus(&mut self) {
+        self.cancel_sloppy_focus();
+        self.apply_layout().ok();
+        self.refocus_if_hidden();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Tags {
+        tags: TagMask,
+    }
+
+    fn parse(tags: &str) -> Result<TagMask, String> {
+        toml::from_str::<Tags>(&format!("tags = {tags}"))
+            .map(|parsed| parsed.tags)
+            .map_err(|err| err.message().to_string())
+    }
+
+    #[test]
+    fn tag_mask_from_number_or_list() {
+        assert_eq!(parse("1"), Ok(TagMask(0b1)));
+        assert_eq!(parse("9"), Ok(TagMask(1 << 8)));
+        assert_eq!(parse("[1, 3, 3]"), Ok(TagMask(0b101)));
+    }
+
+    #[test]
+    fn tag_mask_rejects_out_of_range_tags() {
+        assert_eq!(parse("0"), Err("tag must be between 1 and 9, got 0".into()));
+        assert_eq!(
+            parse("[2, 10]"),
+            Err("tag must be between 1 and 9, got 10".into())
+        );
+        assert_eq!(parse("[]"), Err("at least one tag is required".into()));
+    }
 }